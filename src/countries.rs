@@ -0,0 +1,270 @@
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, PartialOrd, Ord)]
+pub enum Continent {
+    Africa,
+    Asia,
+    Europe,
+    NorthAmerica,
+    Oceania,
+    SouthAmerica,
+}
+
+impl Continent {
+    pub fn pretty_name(&self) -> &'static str {
+        match self {
+            Continent::Africa => "Africa",
+            Continent::Asia => "Asia",
+            Continent::Europe => "Europe",
+            Continent::NorthAmerica => "North America",
+            Continent::Oceania => "Oceania",
+            Continent::SouthAmerica => "South America",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Country {
+    pub iso2: &'static str,
+    pub name: &'static str,
+    pub continent: Continent,
+}
+
+pub fn country_by_iso2(iso2: &str) -> Option<Country> {
+    COUNTRIES
+        .iter()
+        .find(|(code, _, _)| code.eq_ignore_ascii_case(iso2))
+        .map(to_country)
+}
+
+pub fn country_by_name(name: &str) -> Option<Country> {
+    COUNTRIES
+        .iter()
+        .find(|(_, country_name, _)| country_name.eq_ignore_ascii_case(name.trim()))
+        .map(to_country)
+}
+
+/// Flag emoji of a country, built from the regional indicator symbols of its ISO code.
+pub fn flag(iso2: &str) -> String {
+    iso2.chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .filter_map(|c| char::from_u32(0x1F1E6 + (c.to_ascii_uppercase() as u32 - 'A' as u32)))
+        .collect()
+}
+
+fn to_country(entry: &(&'static str, &'static str, Continent)) -> Country {
+    Country {
+        iso2: entry.0,
+        name: entry.1,
+        continent: entry.2,
+    }
+}
+
+// countries recognized by the WCA: ISO 3166-1 alpha-2 code, name as used by the WCA, continent
+const COUNTRIES: [(&str, &str, Continent); 199] = [
+    ("AF", "Afghanistan", Continent::Asia),
+    ("AL", "Albania", Continent::Europe),
+    ("DZ", "Algeria", Continent::Africa),
+    ("AD", "Andorra", Continent::Europe),
+    ("AO", "Angola", Continent::Africa),
+    ("AG", "Antigua and Barbuda", Continent::NorthAmerica),
+    ("AR", "Argentina", Continent::SouthAmerica),
+    ("AM", "Armenia", Continent::Europe),
+    ("AU", "Australia", Continent::Oceania),
+    ("AT", "Austria", Continent::Europe),
+    ("AZ", "Azerbaijan", Continent::Europe),
+    ("BS", "Bahamas", Continent::NorthAmerica),
+    ("BH", "Bahrain", Continent::Asia),
+    ("BD", "Bangladesh", Continent::Asia),
+    ("BB", "Barbados", Continent::NorthAmerica),
+    ("BY", "Belarus", Continent::Europe),
+    ("BE", "Belgium", Continent::Europe),
+    ("BZ", "Belize", Continent::NorthAmerica),
+    ("BJ", "Benin", Continent::Africa),
+    ("BT", "Bhutan", Continent::Asia),
+    ("BO", "Bolivia", Continent::SouthAmerica),
+    ("BA", "Bosnia and Herzegovina", Continent::Europe),
+    ("BW", "Botswana", Continent::Africa),
+    ("BR", "Brazil", Continent::SouthAmerica),
+    ("BN", "Brunei", Continent::Asia),
+    ("BG", "Bulgaria", Continent::Europe),
+    ("BF", "Burkina Faso", Continent::Africa),
+    ("BI", "Burundi", Continent::Africa),
+    ("KH", "Cambodia", Continent::Asia),
+    ("CM", "Cameroon", Continent::Africa),
+    ("CA", "Canada", Continent::NorthAmerica),
+    ("CV", "Cabo Verde", Continent::Africa),
+    ("CF", "Central African Republic", Continent::Africa),
+    ("TD", "Chad", Continent::Africa),
+    ("CL", "Chile", Continent::SouthAmerica),
+    ("CN", "China", Continent::Asia),
+    ("CO", "Colombia", Continent::SouthAmerica),
+    ("KM", "Comoros", Continent::Africa),
+    ("CG", "Congo", Continent::Africa),
+    ("CR", "Costa Rica", Continent::NorthAmerica),
+    ("CI", "Côte d'Ivoire", Continent::Africa),
+    ("HR", "Croatia", Continent::Europe),
+    ("CU", "Cuba", Continent::NorthAmerica),
+    ("CY", "Cyprus", Continent::Europe),
+    ("CZ", "Czech Republic", Continent::Europe),
+    (
+        "KP",
+        "Democratic People's Republic of Korea",
+        Continent::Asia,
+    ),
+    ("CD", "Democratic Republic of the Congo", Continent::Africa),
+    ("DK", "Denmark", Continent::Europe),
+    ("DJ", "Djibouti", Continent::Africa),
+    ("DM", "Dominica", Continent::NorthAmerica),
+    ("DO", "Dominican Republic", Continent::NorthAmerica),
+    ("EC", "Ecuador", Continent::SouthAmerica),
+    ("EG", "Egypt", Continent::Africa),
+    ("SV", "El Salvador", Continent::NorthAmerica),
+    ("GQ", "Equatorial Guinea", Continent::Africa),
+    ("ER", "Eritrea", Continent::Africa),
+    ("EE", "Estonia", Continent::Europe),
+    ("SZ", "Eswatini", Continent::Africa),
+    ("ET", "Ethiopia", Continent::Africa),
+    ("FM", "Federated States of Micronesia", Continent::Oceania),
+    ("FJ", "Fiji", Continent::Oceania),
+    ("FI", "Finland", Continent::Europe),
+    ("FR", "France", Continent::Europe),
+    ("GA", "Gabon", Continent::Africa),
+    ("GM", "Gambia", Continent::Africa),
+    ("GE", "Georgia", Continent::Europe),
+    ("DE", "Germany", Continent::Europe),
+    ("GH", "Ghana", Continent::Africa),
+    ("GR", "Greece", Continent::Europe),
+    ("GD", "Grenada", Continent::NorthAmerica),
+    ("GT", "Guatemala", Continent::NorthAmerica),
+    ("GN", "Guinea", Continent::Africa),
+    ("GW", "Guinea Bissau", Continent::Africa),
+    ("GY", "Guyana", Continent::SouthAmerica),
+    ("HT", "Haiti", Continent::NorthAmerica),
+    ("HN", "Honduras", Continent::NorthAmerica),
+    ("HK", "Hong Kong, China", Continent::Asia),
+    ("HU", "Hungary", Continent::Europe),
+    ("IS", "Iceland", Continent::Europe),
+    ("IN", "India", Continent::Asia),
+    ("ID", "Indonesia", Continent::Asia),
+    ("IR", "Iran", Continent::Asia),
+    ("IQ", "Iraq", Continent::Asia),
+    ("IE", "Ireland", Continent::Europe),
+    ("IL", "Israel", Continent::Europe),
+    ("IT", "Italy", Continent::Europe),
+    ("JM", "Jamaica", Continent::NorthAmerica),
+    ("JP", "Japan", Continent::Asia),
+    ("JO", "Jordan", Continent::Asia),
+    ("KZ", "Kazakhstan", Continent::Asia),
+    ("KE", "Kenya", Continent::Africa),
+    ("KI", "Kiribati", Continent::Oceania),
+    ("XK", "Kosovo", Continent::Europe),
+    ("KW", "Kuwait", Continent::Asia),
+    ("KG", "Kyrgyzstan", Continent::Asia),
+    ("LA", "Laos", Continent::Asia),
+    ("LV", "Latvia", Continent::Europe),
+    ("LB", "Lebanon", Continent::Asia),
+    ("LS", "Lesotho", Continent::Africa),
+    ("LR", "Liberia", Continent::Africa),
+    ("LY", "Libya", Continent::Africa),
+    ("LI", "Liechtenstein", Continent::Europe),
+    ("LT", "Lithuania", Continent::Europe),
+    ("LU", "Luxembourg", Continent::Europe),
+    ("MO", "Macau, China", Continent::Asia),
+    ("MG", "Madagascar", Continent::Africa),
+    ("MW", "Malawi", Continent::Africa),
+    ("MY", "Malaysia", Continent::Asia),
+    ("MV", "Maldives", Continent::Asia),
+    ("ML", "Mali", Continent::Africa),
+    ("MT", "Malta", Continent::Europe),
+    ("MH", "Marshall Islands", Continent::Oceania),
+    ("MR", "Mauritania", Continent::Africa),
+    ("MU", "Mauritius", Continent::Africa),
+    ("MX", "Mexico", Continent::NorthAmerica),
+    ("MD", "Moldova", Continent::Europe),
+    ("MC", "Monaco", Continent::Europe),
+    ("MN", "Mongolia", Continent::Asia),
+    ("ME", "Montenegro", Continent::Europe),
+    ("MA", "Morocco", Continent::Africa),
+    ("MZ", "Mozambique", Continent::Africa),
+    ("MM", "Myanmar", Continent::Asia),
+    ("NA", "Namibia", Continent::Africa),
+    ("NR", "Nauru", Continent::Oceania),
+    ("NP", "Nepal", Continent::Asia),
+    ("NL", "Netherlands", Continent::Europe),
+    ("NZ", "New Zealand", Continent::Oceania),
+    ("NI", "Nicaragua", Continent::NorthAmerica),
+    ("NE", "Niger", Continent::Africa),
+    ("NG", "Nigeria", Continent::Africa),
+    ("MK", "North Macedonia", Continent::Europe),
+    ("NO", "Norway", Continent::Europe),
+    ("OM", "Oman", Continent::Asia),
+    ("PK", "Pakistan", Continent::Asia),
+    ("PW", "Palau", Continent::Oceania),
+    ("PS", "Palestine", Continent::Asia),
+    ("PA", "Panama", Continent::NorthAmerica),
+    ("PG", "Papua New Guinea", Continent::Oceania),
+    ("PY", "Paraguay", Continent::SouthAmerica),
+    ("PE", "Peru", Continent::SouthAmerica),
+    ("PH", "Philippines", Continent::Asia),
+    ("PL", "Poland", Continent::Europe),
+    ("PT", "Portugal", Continent::Europe),
+    ("QA", "Qatar", Continent::Asia),
+    ("KR", "Republic of Korea", Continent::Asia),
+    ("RO", "Romania", Continent::Europe),
+    ("RU", "Russia", Continent::Europe),
+    ("RW", "Rwanda", Continent::Africa),
+    ("KN", "Saint Kitts and Nevis", Continent::NorthAmerica),
+    ("LC", "Saint Lucia", Continent::NorthAmerica),
+    (
+        "VC",
+        "Saint Vincent and the Grenadines",
+        Continent::NorthAmerica,
+    ),
+    ("WS", "Samoa", Continent::Oceania),
+    ("SM", "San Marino", Continent::Europe),
+    ("ST", "São Tomé and Príncipe", Continent::Africa),
+    ("SA", "Saudi Arabia", Continent::Asia),
+    ("SN", "Senegal", Continent::Africa),
+    ("RS", "Serbia", Continent::Europe),
+    ("SC", "Seychelles", Continent::Africa),
+    ("SL", "Sierra Leone", Continent::Africa),
+    ("SG", "Singapore", Continent::Asia),
+    ("SK", "Slovakia", Continent::Europe),
+    ("SI", "Slovenia", Continent::Europe),
+    ("SB", "Solomon Islands", Continent::Oceania),
+    ("SO", "Somalia", Continent::Africa),
+    ("ZA", "South Africa", Continent::Africa),
+    ("SS", "South Sudan", Continent::Africa),
+    ("ES", "Spain", Continent::Europe),
+    ("LK", "Sri Lanka", Continent::Asia),
+    ("SD", "Sudan", Continent::Africa),
+    ("SR", "Suriname", Continent::SouthAmerica),
+    ("SE", "Sweden", Continent::Europe),
+    ("CH", "Switzerland", Continent::Europe),
+    ("SY", "Syria", Continent::Asia),
+    ("TW", "Chinese Taipei", Continent::Asia),
+    ("TJ", "Tajikistan", Continent::Asia),
+    ("TZ", "Tanzania", Continent::Africa),
+    ("TH", "Thailand", Continent::Asia),
+    ("TL", "Timor-Leste", Continent::Asia),
+    ("TG", "Togo", Continent::Africa),
+    ("TO", "Tonga", Continent::Oceania),
+    ("TT", "Trinidad and Tobago", Continent::NorthAmerica),
+    ("TN", "Tunisia", Continent::Africa),
+    ("TR", "Turkey", Continent::Europe),
+    ("TM", "Turkmenistan", Continent::Asia),
+    ("TV", "Tuvalu", Continent::Oceania),
+    ("UG", "Uganda", Continent::Africa),
+    ("UA", "Ukraine", Continent::Europe),
+    ("AE", "United Arab Emirates", Continent::Asia),
+    ("GB", "United Kingdom", Continent::Europe),
+    ("US", "United States", Continent::NorthAmerica),
+    ("UY", "Uruguay", Continent::SouthAmerica),
+    ("UZ", "Uzbekistan", Continent::Asia),
+    ("VU", "Vanuatu", Continent::Oceania),
+    ("VA", "Vatican City", Continent::Europe),
+    ("VE", "Venezuela", Continent::SouthAmerica),
+    ("VN", "Vietnam", Continent::Asia),
+    ("YE", "Yemen", Continent::Asia),
+    ("ZM", "Zambia", Continent::Africa),
+    ("ZW", "Zimbabwe", Continent::Africa),
+];
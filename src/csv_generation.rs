@@ -0,0 +1,223 @@
+use std::fs;
+use std::path::Path;
+
+use crate::datastructures::{Event, RecordType};
+use crate::formatting::{format_percentage, format_result};
+use crate::groups::{EventGroups, GroupSettings};
+use crate::report::{EventReport, Renderer, Report};
+use crate::wcoerror::WCOError;
+
+/// `<event>.csv` for every event and `competitors.csv`, for spreadsheets.
+pub struct CsvRenderer;
+
+impl Renderer for CsvRenderer {
+    fn render(&self, report: &Report, report_dir: &Path) -> Result<(), WCOError> {
+        for event_report in &report.events {
+            fs::write(
+                report_dir.join(format!("{}.csv", event_report.event.code_name())),
+                generate_event_csv(event_report),
+            )?;
+        }
+        fs::write(
+            report_dir.join("competitors.csv"),
+            generate_competitors_csv(report),
+        )?;
+        Ok(())
+    }
+}
+
+/// Same rows as the table of the event page.
+pub fn generate_event_csv(event_report: &EventReport) -> String {
+    let event = &event_report.event;
+    let ranking = event_report.ranking;
+    let participants: Vec<_> = event_report.participants().collect();
+    let show_chances = participants
+        .iter()
+        .any(|comp| comp.chances.contains_key(event));
+
+    let mut header = vec!["Rank", "Name", "WCA ID", "Country", "PR Single"];
+    if event.has_average() {
+        header.push("PR Average");
+    }
+    header.extend(["WR", "CR", "NR"]);
+    if show_chances {
+        header.extend(["Win %", "Podium %", "Advance %"]);
+    }
+    let mut csv = csv_line(&header);
+    for (index, competitor) in participants.iter().enumerate() {
+        let record = competitor.personal_records.get(event);
+        let format_value = |record_type| {
+            record
+                .and_then(|pr| pr.get(record_type))
+                .map(format_result)
+                .unwrap_or_default()
+        };
+        let ranks = record.and_then(|pr| pr.ranks(ranking));
+        let mut fields = vec![
+            match index < event_report.ranked.len() {
+                true => (index + 1).to_string(),
+                false => String::new(),
+            },
+            competitor.name.clone(),
+            competitor.wca_id.clone().unwrap_or_default(),
+            competitor.country_iso2.clone().unwrap_or_default(),
+            format_value(RecordType::Single),
+        ];
+        if event.has_average() {
+            fields.push(format_value(RecordType::Average));
+        }
+        for rank in [
+            ranks.map(|ranks| ranks.world),
+            ranks.map(|ranks| ranks.continent),
+            ranks.map(|ranks| ranks.country),
+        ] {
+            fields.push(rank.map(|rank| rank.to_string()).unwrap_or_default());
+        }
+        if show_chances {
+            let chances = competitor.chances.get(event).copied().unwrap_or_default();
+            fields.push(format_percentage(chances.win));
+            fields.push(format_percentage(chances.podium));
+            fields.push(chances.advance.map(format_percentage).unwrap_or_default());
+        }
+        csv += &csv_line(&fields.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+    csv
+}
+
+/// One line per competitor with their PR single and, for events with averages, PR average in
+/// every event they registered for. Events without any PR are marked as "registered".
+pub fn generate_competitors_csv(report: &Report) -> String {
+    let record_types = |event: &Event| match event.has_average() {
+        true => &[RecordType::Single, RecordType::Average][..],
+        false => &[RecordType::Single][..],
+    };
+    let mut header: Vec<String> = ["Name", "WCA ID", "Country", "Gender"]
+        .map(str::to_owned)
+        .to_vec();
+    for event_report in &report.events {
+        let event = &event_report.event;
+        for record_type in record_types(event) {
+            header.push(format!("{} {}", event.code_name(), record_type.name()));
+        }
+    }
+    let mut csv = csv_line(&header.iter().map(String::as_str).collect::<Vec<_>>());
+    for competitor in report.competitors {
+        let mut fields = vec![
+            competitor.name.clone(),
+            competitor.wca_id.clone().unwrap_or_default(),
+            competitor.country_iso2.clone().unwrap_or_default(),
+            competitor
+                .gender
+                .map(|gender| gender.pretty_name().to_owned())
+                .unwrap_or_default(),
+        ];
+        for event_report in &report.events {
+            let event = &event_report.event;
+            let record = competitor.personal_records.get(event);
+            for record_type in record_types(event) {
+                fields.push(match (competitor.events.contains(event), record) {
+                    (true, Some(record)) => record
+                        .get(*record_type)
+                        .map(format_result)
+                        .unwrap_or_default(),
+                    (true, None) => "registered".to_owned(),
+                    (false, _) => String::new(),
+                });
+            }
+        }
+        csv += &csv_line(&fields.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+    csv
+}
+
+/// One line per competitor and group.
+pub fn generate_groups_csv(event_groups: &[EventGroups], settings: &GroupSettings) -> String {
+    let mut csv = csv_line(&["Event", "Group", "Name", "WCA ID", "Staff", "PR"]);
+    for event_groups in event_groups {
+        for (index, group) in event_groups.groups.iter().enumerate() {
+            for competitor in group {
+                let record = competitor
+                    .personal_records
+                    .get(&event_groups.event)
+                    .and_then(|pr| pr.get(event_groups.ranking))
+                    .map(format_result)
+                    .unwrap_or_default();
+                csv += &csv_line(&[
+                    event_groups.event.code_name(),
+                    &(index + 1).to_string(),
+                    &competitor.name,
+                    competitor.wca_id.as_deref().unwrap_or_default(),
+                    if settings.is_staff(competitor) {
+                        "yes"
+                    } else {
+                        ""
+                    },
+                    &record,
+                ]);
+            }
+        }
+    }
+    csv
+}
+
+/// Joins the fields with commas, quoting fields that contain commas, quotes or line breaks.
+fn csv_line(fields: &[&str]) -> String {
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|field| match field.contains([',', '"', '\n', '\r']) {
+            true => format!("\"{}\"", field.replace('"', "\"\"")),
+            false => field.to_string(),
+        })
+        .collect();
+    fields.join(",") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::datastructures::{Competition, Competitor, PersonalRecord, ResultValue};
+
+    fn time(seconds: u64) -> ResultValue {
+        ResultValue::Time(Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn competitors_csv_has_single_and_average_columns() {
+        let competition = Competition {
+            name: "Test Open 2024".to_owned(),
+            start_date: None,
+            end_date: None,
+            events: vec![],
+            schedule: vec![],
+        };
+        let mut alice = Competitor::new(
+            "Alice".to_owned(),
+            Some("2015ALIC01".to_owned()),
+            vec![Event::Ev333, Event::Ev333bf],
+        );
+        let mut record = PersonalRecord::default();
+        record.set(RecordType::Single, time(8), None);
+        alice.personal_records.insert(Event::Ev333, record);
+        // a single without an average stays in the single column
+        alice.personal_records.insert(Event::Ev333bf, record);
+        let bob = Competitor::new("Bob".to_owned(), None, vec![Event::Ev333]);
+        let competitors = [alice, bob];
+        let report = Report::new(
+            &competition,
+            &competitors,
+            &[
+                (Event::Ev333bf, RecordType::Single),
+                (Event::Ev333, RecordType::Average),
+            ],
+        );
+
+        assert_eq!(
+            generate_competitors_csv(&report),
+            "Name,WCA ID,Country,Gender,333 Single,333 Average,333bf Single,333bf Average\n\
+            Alice,2015ALIC01,,,8.00,,8.00,\n\
+            Bob,,,,registered,registered,,\n"
+        );
+    }
+}
@@ -1,4 +1,246 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use indicatif::ProgressBar;
+use serde::Serialize;
+
+use crate::datastructures::{Competitor, Event, Gender, PersonalRecord, ResultValue};
+use crate::wcoerror::WCOError;
+use fetcher::Fetcher;
+
+pub mod cache;
 pub mod competitorslist;
+pub mod fetcher;
 pub mod pr_data_random;
 pub mod pr_data_unofficialapi;
+pub mod pr_data_wcaexport;
 pub mod pr_data_wcawebsite;
+pub mod source_registry;
+#[cfg(test)]
+mod stub_server;
+pub mod wcif;
+
+/// Describes which kinds of data a [`PrSource`] is able to deliver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SourceCapabilities {
+    pub singles: bool,
+    pub averages: bool,
+    pub ranks: bool,
+    /// The records are made up and must not be mistaken for real data.
+    pub synthetic: bool,
+}
+
+/// Everything a source may need to construct itself.
+pub struct SourceConfig {
+    pub fetcher: Arc<Fetcher>,
+    pub wca_url: String,
+    pub api_url: String,
+    pub export_path: Option<PathBuf>,
+    /// WCA IDs whose recent attempts are needed, `None` if no attempts are needed.
+    /// Sources with expensive history lookups only load these.
+    pub history_for: Option<HashSet<String>>,
+}
+
+/// Everything a source knows about a person. Personal details are `None` if the source does not
+/// provide them.
+#[derive(Debug, Default)]
+pub struct PersonData {
+    pub records: HashMap<Event, PersonalRecord>,
+    pub country_iso2: Option<String>,
+    pub gender: Option<Gender>,
+    /// Most recent attempts per event, newest first, if requested via `SourceConfig::history_for`
+    pub recent_attempts: RecentAttempts,
+}
+
+/// Attempts per event, newest first. `None` is a DNF or DNS.
+pub type RecentAttempts = HashMap<Event, Vec<Option<ResultValue>>>;
+
+/// Number of attempts per event that sources keep in `PersonData::recent_attempts`.
+pub const RECENT_ATTEMPT_COUNT: usize = 50;
+
+/// A backend that knows how to look up the personal records of a WCA competitor.
+pub trait PrSource: Send + Sync {
+    fn capabilities(&self) -> SourceCapabilities;
+
+    /// Retrieves all personal records of the person with the given WCA ID in one go.
+    fn retrieve_records(&self, wca_id: &str) -> Result<PersonData, WCOError>;
+}
+
+/// Retrieves the records of all competitors with a WCA ID, using up to `jobs` worker threads.
+/// Only records for events the competitor is registered for are kept. Personal details are only
+/// filled in if the competition data did not contain them already. With `keep_going`, a
+/// failed retrieval is stored in the competitor's `retrieval_error` instead of aborting.
+pub fn retrieve_all_records(
+    source: &dyn PrSource,
+    competitors: &mut [Competitor],
+    jobs: usize,
+    keep_going: bool,
+    bar: &ProgressBar,
+) -> Result<(), WCOError> {
+    let wca_ids: Vec<_> = competitors.iter().map(|comp| comp.wca_id.clone()).collect();
+    let next_job = AtomicUsize::new(0);
+    let cancelled = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.max(1) {
+            let sender = sender.clone();
+            let (wca_ids, next_job, cancelled) = (&wca_ids, &next_job, &cancelled);
+            scope.spawn(move || {
+                while !cancelled.load(Ordering::Relaxed) {
+                    let index = next_job.fetch_add(1, Ordering::Relaxed);
+                    let Some(wca_id) = wca_ids.get(index) else {
+                        break;
+                    };
+                    let result = wca_id
+                        .as_deref()
+                        .map(|id| source.retrieve_records(id))
+                        .transpose();
+                    if sender.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for (index, result) in receiver {
+            match result {
+                Ok(Some(mut person)) => {
+                    let competitor = &mut competitors[index];
+                    person
+                        .records
+                        .retain(|event, _| competitor.events.contains(event));
+                    competitor.personal_records = person.records;
+                    person
+                        .recent_attempts
+                        .retain(|event, _| competitor.events.contains(event));
+                    competitor.recent_attempts = person.recent_attempts;
+                    if competitor.country_iso2.is_none() {
+                        competitor.country_iso2 = person.country_iso2;
+                    }
+                    competitor.gender = competitor.gender.or(person.gender);
+                }
+                Ok(None) => {}
+                Err(e) if keep_going => competitors[index].retrieval_error = Some(e.to_string()),
+                Err(e) => {
+                    cancelled.store(true, Ordering::Relaxed);
+                    return Err(e);
+                }
+            }
+            bar.inc(1);
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use fetcher::RetryPolicy;
+    use stub_server::StubServer;
+
+    /// Source that downloads `/persons/<id>` and stores the body as the country.
+    struct StubSource {
+        fetcher: Fetcher,
+        url: String,
+    }
+
+    impl PrSource for StubSource {
+        fn capabilities(&self) -> SourceCapabilities {
+            SourceCapabilities {
+                singles: true,
+                averages: true,
+                ranks: false,
+                synthetic: true,
+            }
+        }
+
+        fn retrieve_records(&self, wca_id: &str) -> Result<PersonData, WCOError> {
+            let body = self
+                .fetcher
+                .get_text(&format!("{}/persons/{}", self.url, wca_id))?;
+            Ok(PersonData {
+                country_iso2: Some(body),
+                ..PersonData::default()
+            })
+        }
+    }
+
+    fn stub_source(server: &StubServer) -> StubSource {
+        let retry_policy = RetryPolicy {
+            retries: 0,
+            initial_backoff: Duration::ZERO,
+        };
+        StubSource {
+            fetcher: Fetcher::new(0.0, Duration::from_secs(5), retry_policy, None).unwrap(),
+            url: server.url.clone(),
+        }
+    }
+
+    fn competitors() -> Vec<Competitor> {
+        (0..12)
+            .map(|index| {
+                let wca_id = (index % 4 != 0).then(|| format!("2020TEST{:02}", index));
+                Competitor::new(format!("Competitor {}", index), wca_id, vec![Event::Ev333])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn counts_every_competitor_with_parallel_jobs() {
+        let server = StubServer::start(|_, path| (200, path.replace("/persons/2020TEST", "C")));
+        let mut competitors = competitors();
+        let bar = ProgressBar::hidden();
+        retrieve_all_records(&stub_source(&server), &mut competitors, 4, false, &bar).unwrap();
+
+        assert_eq!(bar.position(), competitors.len() as u64);
+        // newcomers are counted, but not requested
+        let mut paths = server.paths();
+        paths.sort();
+        let expected: Vec<_> = competitors
+            .iter()
+            .filter_map(|comp| Some(format!("/persons/{}", comp.wca_id.as_ref()?)))
+            .collect();
+        assert_eq!(paths, expected);
+        for (index, competitor) in competitors.iter().enumerate() {
+            let expected = competitor.wca_id.as_ref().map(|_| format!("C{:02}", index));
+            assert_eq!(competitor.country_iso2, expected);
+        }
+    }
+
+    #[test]
+    fn keeps_going_after_failed_retrievals() {
+        let server = StubServer::start(|_, path| match path.ends_with("05") {
+            true => (404, String::new()),
+            false => (200, String::new()),
+        });
+        let mut competitors = competitors();
+        let bar = ProgressBar::hidden();
+        retrieve_all_records(&stub_source(&server), &mut competitors, 3, true, &bar).unwrap();
+
+        assert_eq!(bar.position(), competitors.len() as u64);
+        let failed: Vec<_> = competitors
+            .iter()
+            .filter(|comp| comp.retrieval_error.is_some())
+            .map(|comp| comp.name.as_str())
+            .collect();
+        assert_eq!(failed, ["Competitor 5"]);
+    }
+
+    #[test]
+    fn stops_at_the_first_failure_without_keep_going() {
+        let server = StubServer::start(|_, _| (404, String::new()));
+        let mut competitors = competitors();
+        let bar = ProgressBar::hidden();
+        let result = retrieve_all_records(&stub_source(&server), &mut competitors, 2, false, &bar);
+
+        assert!(result.is_err());
+        assert!(bar.position() < competitors.len() as u64);
+    }
+}
@@ -0,0 +1,179 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+use crate::wcoerror::WCOError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Use fresh cache entries, download everything else.
+    Normal,
+    /// Only use cache entries, regardless of their age. Never touch the network.
+    Offline,
+    /// Ignore existing entries, download everything and update the cache.
+    Refresh,
+}
+
+/// Persistent cache of downloaded documents, one file per URL.
+pub struct HttpCache {
+    dir: PathBuf,
+    ttl: Duration,
+    mode: CacheMode,
+}
+
+impl HttpCache {
+    pub fn new(dir: PathBuf, ttl: Duration, mode: CacheMode) -> Result<Self, WCOError> {
+        fs::create_dir_all(&dir)?;
+        Ok(HttpCache { dir, ttl, mode })
+    }
+
+    pub fn default_dir() -> Option<PathBuf> {
+        std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .map(|dir| dir.join("wca_comp_overview"))
+    }
+
+    pub fn mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    pub fn lookup(&self, url: &str) -> Option<String> {
+        if self.mode == CacheMode::Refresh {
+            return None;
+        }
+        let path = self.entry_path(url);
+        let age = fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .unwrap_or_default();
+        match self.mode {
+            CacheMode::Normal if age > self.ttl => None,
+            _ => fs::read_to_string(path).ok(),
+        }
+    }
+
+    pub fn store(&self, url: &str, content: &str) -> Result<(), WCOError> {
+        static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = self.entry_path(url);
+        // write to a temporary file first, so that parallel workers never see partial entries.
+        // '~' is always encoded in keys, so temporary names cannot clash with entries.
+        let tmp_path = self.dir.join(format!(
+            "{}~{}-{}.tmp",
+            Self::key(url),
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&tmp_path, content)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        self.dir.join(Self::key(url))
+    }
+
+    /// File name of the entry of `url`: the URL without its scheme, percent-encoded so that
+    /// different URLs never share an entry.
+    fn key(url: &str) -> String {
+        let mut key = String::new();
+        for byte in url.split_once("://").map_or(url, |(_, rest)| rest).bytes() {
+            match byte {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' => {
+                    key.push(byte as char)
+                }
+                _ => key += &format!("%{:02X}", byte),
+            }
+        }
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    /// Empty cache in a fresh directory, removed again when the test is done.
+    struct TestCache {
+        cache: HttpCache,
+    }
+
+    impl TestCache {
+        fn new(name: &str) -> TestCache {
+            let dir = std::env::temp_dir().join(format!(
+                "wco-cache-test-{}-{}",
+                std::process::id(),
+                name
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            let cache = HttpCache::new(dir, Duration::from_secs(60), CacheMode::Normal).unwrap();
+            TestCache { cache }
+        }
+    }
+
+    impl Drop for TestCache {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.cache.dir);
+        }
+    }
+
+    #[test]
+    fn keys_of_different_urls_differ() {
+        let urls = [
+            "https://www.worldcubeassociation.org/persons/2009ZEMD01",
+            "https://www.worldcubeassociation.org/persons_2009ZEMD01",
+            "https://www.worldcubeassociation.org/persons%2F2009ZEMD01",
+            "https://www.worldcubeassociation.org/persons/2009ZEMD01?a=b",
+            "https://www.worldcubeassociation.org/persons/2009ZEMD01?a_b",
+        ];
+        let mut keys: Vec<_> = urls.iter().map(|url| HttpCache::key(url)).collect();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), urls.len());
+    }
+
+    #[test]
+    fn stores_and_looks_up_entries() {
+        let test_cache = TestCache::new("lookup");
+        let cache = &test_cache.cache;
+        let url = "https://www.worldcubeassociation.org/api/v0/persons/2009ZEMD01";
+        assert_eq!(cache.lookup(url), None);
+        cache.store(url, "document").unwrap();
+        assert_eq!(cache.lookup(url).as_deref(), Some("document"));
+    }
+
+    #[test]
+    fn parallel_stores_keep_entries_intact() {
+        let test_cache = TestCache::new("parallel");
+        let cache = &test_cache.cache;
+        let url = |index: usize| format!("https://www.worldcubeassociation.org/persons/{index}");
+        let content = |index: usize| index.to_string().repeat(10_000);
+        thread::scope(|scope| {
+            for worker in 0..8 {
+                scope.spawn(move || {
+                    for round in 0..20 {
+                        // every worker writes every entry, all entries share the host prefix
+                        let index = (worker + round) % 10;
+                        cache.store(&url(index), &content(index)).unwrap();
+                    }
+                });
+            }
+        });
+        for index in 0..10 {
+            assert_eq!(cache.lookup(&url(index)), Some(content(index)));
+        }
+        let leftovers = fs::read_dir(&cache.dir)
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().ends_with(".tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+}
@@ -1,9 +1,28 @@
-use crate::datastructures::{Competitor, Event};
+use super::fetcher::Fetcher;
+use crate::countries::country_by_name;
+use crate::datastructures::{Competition, Competitor, Event};
 use crate::wcoerror::WCOError;
 use regex::Regex;
 use scraper::{selectable::Selectable, ElementRef, Html, Selector};
 
-pub fn get_competition_title(html: &Html) -> Result<String, WCOError> {
+/// Loads the competition from its registrations page. The page only lists the competition
+/// title and the competitors, so the competition's events come without details.
+pub fn retrieve_competition(
+    fetcher: &Fetcher,
+    registrations_url: &str,
+) -> Result<(Competition, Vec<Competitor>), WCOError> {
+    let competitors_html = Html::parse_document(&fetcher.get_text(registrations_url)?);
+    let competition = Competition {
+        name: get_competition_title(&competitors_html)?,
+        start_date: None,
+        end_date: None,
+        events: vec![],
+        schedule: vec![],
+    };
+    Ok((competition, parse_competitors(&competitors_html)))
+}
+
+fn get_competition_title(html: &Html) -> Result<String, WCOError> {
     let selector = Selector::parse(r#"h3"#).expect("Parsing known selector should not fail");
 
     let h3_headlines: Vec<_> = html.select(&selector).collect();
@@ -16,34 +35,56 @@ pub fn get_competition_title(html: &Html) -> Result<String, WCOError> {
     }
 }
 
-pub fn parse_competitors(competitors_list: &Html) -> Vec<Competitor> {
+fn parse_competitors(competitors_list: &Html) -> Vec<Competitor> {
     let selector =
         Selector::parse(r#"td[class="name"]"#).expect("Parsing known selector should not fail");
     let name_selector = &Selector::parse("a").expect("Parsing known selector should not fail");
     let event_selector = Selector::parse("i").expect("Parsing known selector should not fail");
+    let flag_selector =
+        Selector::parse(r#"[class*="fi-"]"#).expect("Parsing known selector should not fail");
 
     competitors_list
         .select(&selector)
         .map(|element| {
             let name = element.text().collect::<String>().trim().to_owned();
             let wca_id = element
-                .select(&name_selector)
+                .select(name_selector)
                 .next()
                 .and_then(|elem| elem.value().attr("href").and_then(parse_wca_id));
             let all_classes = element
                 .next_siblings()
-                .filter_map(|node| ElementRef::wrap(node))
+                .filter_map(ElementRef::wrap)
                 .flat_map(|elem| {
                     elem.select(&event_selector)
                         .flat_map(|elem| elem.value().classes().collect::<Vec<_>>())
                 })
                 .collect::<Vec<_>>();
             let events = extract_events(&all_classes);
-            Competitor::new(name, wca_id, events)
+            let mut competitor = Competitor::new(name, wca_id, events);
+            competitor.country_iso2 = element
+                .next_siblings()
+                .filter_map(ElementRef::wrap)
+                .find(|elem| elem.value().classes().any(|class| class == "country"))
+                .and_then(|elem| parse_country(elem, &flag_selector));
+            competitor
         })
         .collect()
 }
 
+/// Reads the country from the flag icon class ("fi fi-de") or, if there is none, the country name.
+fn parse_country(country_cell: ElementRef, flag_selector: &Selector) -> Option<String> {
+    let from_flag = country_cell.select(flag_selector).find_map(|flag| {
+        flag.value()
+            .classes()
+            .find_map(|class| class.strip_prefix("fi-"))
+            .filter(|code| code.len() == 2)
+            .map(|code| code.to_ascii_uppercase())
+    });
+    from_flag.or_else(|| {
+        country_by_name(&country_cell.text().collect::<String>()).map(|c| c.iso2.to_owned())
+    })
+}
+
 fn parse_wca_id(profile_url: &str) -> Option<String> {
     let re = Regex::new(r"/persons/([0-9]{4}[A-Z]{4}[0-9]{2})").unwrap();
     re.captures(profile_url).map(|cap| cap[1].to_owned())
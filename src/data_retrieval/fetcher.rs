@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reqwest::blocking::Client;
+use reqwest::{StatusCode, Url};
+
+use super::cache::{CacheMode, HttpCache};
+use crate::wcoerror::WCOError;
+
+/// HTTP client shared by all retrieval code. Requests to the same host are spaced out so that
+/// at most `max_requests_per_second` are sent, no matter how many workers use the fetcher.
+/// If a cache is given, documents are looked up there first and stored after downloading.
+pub struct Fetcher {
+    client: Client,
+    rate_limiter: RateLimiter,
+    retry_policy: RetryPolicy,
+    cache: Option<HttpCache>,
+}
+
+/// Failed requests are retried `retries` times if the failure looks temporary, waiting
+/// `initial_backoff` before the first retry and doubling the wait for every further one.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub initial_backoff: Duration,
+}
+
+impl Fetcher {
+    pub fn new(
+        max_requests_per_second: f64,
+        timeout: Duration,
+        retry_policy: RetryPolicy,
+        cache: Option<HttpCache>,
+    ) -> Result<Self, WCOError> {
+        Ok(Fetcher {
+            client: Client::builder().timeout(timeout).build()?,
+            rate_limiter: RateLimiter::new(max_requests_per_second),
+            retry_policy,
+            cache,
+        })
+    }
+
+    pub fn get_text(&self, url: &str) -> Result<String, WCOError> {
+        if let Some(cache) = &self.cache {
+            if let Some(content) = cache.lookup(url) {
+                return Ok(content);
+            }
+            if cache.mode() == CacheMode::Offline {
+                return Err(WCOError::CacheMiss(url.to_owned()));
+            }
+        }
+        let parsed = Url::parse(url)
+            .map_err(|e| WCOError::ParsingError(format!("Invalid URL \"{}\": {}", url, e)))?;
+        let mut backoff = self.retry_policy.initial_backoff;
+        let mut attempt = 0;
+        let content = loop {
+            self.rate_limiter
+                .wait(parsed.host_str().unwrap_or_default());
+            match self.download(parsed.clone()) {
+                Ok(content) => break content,
+                Err(e) if attempt < self.retry_policy.retries && is_transient(&e) => {
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+        if let Some(cache) = &self.cache {
+            cache.store(url, &content)?;
+        }
+        Ok(content)
+    }
+
+    fn download(&self, url: Url) -> Result<String, reqwest::Error> {
+        self.client.get(url).send()?.error_for_status()?.text()
+    }
+}
+
+fn is_transient(error: &reqwest::Error) -> bool {
+    match error.status() {
+        Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+        None => error.is_timeout() || error.is_connect() || error.is_body(),
+    }
+}
+
+struct RateLimiter {
+    interval: Option<Duration>,
+    next_slots: Mutex<HashMap<String, Instant>>,
+}
+
+impl RateLimiter {
+    fn new(max_requests_per_second: f64) -> Self {
+        let interval = match max_requests_per_second {
+            rps if rps > 0.0 => Some(Duration::from_secs_f64(1.0 / rps)),
+            _ => None,
+        };
+        RateLimiter {
+            interval,
+            next_slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn wait(&self, host: &str) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let slot = {
+            let mut next_slots = self.next_slots.lock().expect("rate limiter lock poisoned");
+            let now = Instant::now();
+            let slot = next_slots
+                .get(host)
+                .copied()
+                .filter(|slot| *slot > now)
+                .unwrap_or(now);
+            next_slots.insert(host.to_owned(), slot + interval);
+            slot
+        };
+        thread::sleep(slot.saturating_duration_since(Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_retrieval::stub_server::StubServer;
+
+    fn fetcher(max_requests_per_second: f64, retries: u32) -> Fetcher {
+        let retry_policy = RetryPolicy {
+            retries,
+            initial_backoff: Duration::from_millis(1),
+        };
+        Fetcher::new(
+            max_requests_per_second,
+            Duration::from_secs(5),
+            retry_policy,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn retries_server_errors_and_rate_limit_responses() {
+        let server = StubServer::start(|index, _| match index {
+            0 => (503, String::new()),
+            1 => (429, String::new()),
+            _ => (200, "document".to_owned()),
+        });
+        let content = fetcher(0.0, 2).get_text(&server.url).unwrap();
+        assert_eq!(content, "document");
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[test]
+    fn gives_up_after_the_configured_retries() {
+        let server = StubServer::start(|_, _| (500, String::new()));
+        assert!(fetcher(0.0, 2).get_text(&server.url).is_err());
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[test]
+    fn does_not_retry_client_errors() {
+        let server = StubServer::start(|_, _| (404, String::new()));
+        assert!(fetcher(0.0, 3).get_text(&server.url).is_err());
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[test]
+    fn spaces_out_requests_of_parallel_workers() {
+        let server = StubServer::start(|_, _| (200, String::new()));
+        let fetcher = fetcher(20.0, 0);
+        let start = Instant::now();
+        thread::scope(|scope| {
+            for _ in 0..5 {
+                scope.spawn(|| fetcher.get_text(&server.url).unwrap());
+            }
+        });
+        // the first request is sent immediately, every further one 50ms after the previous one
+        let arrivals = server.arrival_times();
+        assert_eq!(arrivals.len(), 5);
+        assert!(arrivals[4].duration_since(start) >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn rate_limits_hosts_independently() {
+        let limiter = RateLimiter::new(10.0);
+        let start = Instant::now();
+        limiter.wait("a.example");
+        limiter.wait("b.example");
+        assert!(start.elapsed() < Duration::from_millis(100));
+        limiter.wait("a.example");
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
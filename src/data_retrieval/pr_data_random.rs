@@ -1,17 +1,92 @@
+use std::collections::HashMap;
 use std::time::Duration;
 
-use crate::datastructures::{Competitor, Event};
+use super::{PersonData, PrSource, SourceCapabilities, RECENT_ATTEMPT_COUNT};
+use crate::datastructures::{Event, PersonalRecord, Ranks, RecordType, ResultValue};
+use crate::wcoerror::WCOError;
 use rand::prelude::*;
 
-pub fn set_random_competitor_pr(competitor: &mut Competitor, event: Event) {
-    let mut rng = rand::thread_rng();
-    if let Some(_) = &mut competitor.wca_id {
-        competitor.personal_records.insert(
-            event,
-            Duration::new(
-                rng.gen_range(7..40),
-                rng.gen_range(10..100) * 10 * 1000 * 1000,
-            ),
-        );
+pub struct RandomSource;
+
+impl PrSource for RandomSource {
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            singles: true,
+            averages: true,
+            ranks: true,
+            synthetic: true,
+        }
+    }
+
+    fn retrieve_records(&self, _wca_id: &str) -> Result<PersonData, WCOError> {
+        let mut rng = rand::thread_rng();
+        let records: HashMap<_, _> = Event::ALL
+            .iter()
+            .map(|event| {
+                let single = match event {
+                    Event::Ev333fm => ResultValue::Moves(rng.gen_range(2000..6000)),
+                    Event::Ev333mbf => {
+                        let attempted = rng.gen_range(2..20);
+                        ResultValue::MultiBlind {
+                            solved: rng.gen_range(attempted / 2 + 1..=attempted),
+                            attempted,
+                            time: Duration::from_secs(rng.gen_range(600..3600)),
+                        }
+                    }
+                    _ => ResultValue::Time(Duration::new(
+                        rng.gen_range(7..40),
+                        rng.gen_range(10..100) * 10 * 1000 * 1000,
+                    )),
+                };
+                let average = match (single, event.has_average()) {
+                    (ResultValue::Time(time), true) => {
+                        Some(ResultValue::Time(time.mul_f64(rng.gen_range(1.05..1.3))))
+                    }
+                    (ResultValue::Moves(moves), true) => {
+                        Some(ResultValue::Moves(moves + rng.gen_range(100..800)))
+                    }
+                    _ => None,
+                };
+                let mut random_ranks = || {
+                    let world = rng.gen_range(1..100_000);
+                    let continent = rng.gen_range(1..=world);
+                    Some(Ranks {
+                        world,
+                        continent,
+                        country: rng.gen_range(1..=continent),
+                    })
+                };
+                let mut record = PersonalRecord::default();
+                record.set(RecordType::Single, single, random_ranks());
+                if let Some(average) = average {
+                    record.set(RecordType::Average, average, random_ranks());
+                }
+                (*event, record)
+            })
+            .collect();
+        let recent_attempts = records
+            .iter()
+            .filter_map(|(event, record)| {
+                let average = record.get(RecordType::Average)?;
+                let attempts = (0..RECENT_ATTEMPT_COUNT)
+                    .map(|_| match average {
+                        _ if rng.gen_bool(0.05) => None,
+                        ResultValue::Time(time) => {
+                            Some(ResultValue::Time(time.mul_f64(rng.gen_range(0.9..1.2))))
+                        }
+                        ResultValue::Moves(moves) => Some(ResultValue::Moves(
+                            (*moves as f64 * rng.gen_range(0.85..1.2)) as u32 / 100 * 100,
+                        )),
+                        multiblind => Some(*multiblind),
+                    })
+                    .collect();
+                Some((*event, attempts))
+            })
+            .collect();
+        Ok(PersonData {
+            records,
+            recent_attempts,
+            ..PersonData::default()
+        })
     }
 }
@@ -1,12 +1,20 @@
-use crate::datastructures::{Competitor, Event};
+use super::fetcher::Fetcher;
+use super::{
+    PersonData, PrSource, RecentAttempts, SourceCapabilities, SourceConfig, RECENT_ATTEMPT_COUNT,
+};
+use crate::datastructures::{Event, Gender, PersonalRecord, Ranks, RecordType, ResultValue};
 use crate::wcoerror::WCOError;
-use reqwest::blocking::get;
 use serde::Deserialize;
-use std::time::Duration;
+use std::{collections::HashMap, sync::Arc};
 
 #[derive(Deserialize)]
 struct Person {
+    country: Option<String>,
+    gender: Option<String>,
     rank: Rank,
+    /// Results by competition ID and event ID, each a list of rounds with their "solves".
+    /// Kept untyped, so that format changes only affect the solve history.
+    results: Option<serde_json::Value>,
 }
 
 #[derive(Deserialize)]
@@ -20,28 +28,147 @@ struct Rank {
 struct PR {
     best: u32,
     eventId: String,
+    rank: Option<PRRank>,
 }
 
-pub fn retrieve_competitor_pr(competitor: &mut Competitor, event: Event) -> Result<(), WCOError> {
-    if let Some(id) = &mut competitor.wca_id {
-        let url = format!("https://raw.githubusercontent.com/robiningelbrecht/wca-rest-api/master/api/persons/{}.json", id);
-        let json: Person = serde_json::from_str(&get(url)?.text()?)?;
-        if let Some(avg) = parse_pr_json(&json, event) {
-            competitor.personal_records.insert(event, avg);
+#[derive(Deserialize)]
+struct PRRank {
+    world: u32,
+    continent: u32,
+    country: u32,
+}
+
+pub struct UnofficialApiSource {
+    fetcher: Arc<Fetcher>,
+    base_url: String,
+}
+
+impl UnofficialApiSource {
+    pub fn new(config: &SourceConfig) -> Self {
+        UnofficialApiSource {
+            fetcher: Arc::clone(&config.fetcher),
+            base_url: config.api_url.trim_end_matches('/').to_owned(),
         }
     }
-    Ok(())
 }
 
-fn parse_pr_json(competitor_json: &Person, event: Event) -> Option<Duration> {
-    let results = match event.use_average() {
-        true => &competitor_json.rank.averages,
-        false => &competitor_json.rank.singles,
-    };
-    results
+impl PrSource for UnofficialApiSource {
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            singles: true,
+            averages: true,
+            ranks: true,
+            synthetic: false,
+        }
+    }
+
+    fn retrieve_records(&self, wca_id: &str) -> Result<PersonData, WCOError> {
+        let url = format!("{}/persons/{}.json", self.base_url, wca_id);
+        let json: Person = serde_json::from_str(&self.fetcher.get_text(&url)?)?;
+        Ok(PersonData {
+            records: parse_pr_json(&json),
+            country_iso2: json.country.clone(),
+            gender: json
+                .gender
+                .as_deref()
+                .and_then(|gender| Gender::try_from(gender).ok()),
+            recent_attempts: json
+                .results
+                .as_ref()
+                .map(parse_recent_attempts)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+fn parse_pr_json(competitor_json: &Person) -> HashMap<Event, PersonalRecord> {
+    let mut records: HashMap<Event, PersonalRecord> = HashMap::new();
+    let averages = competitor_json
+        .rank
+        .averages
+        .iter()
+        .map(|pr| (pr, RecordType::Average));
+    let singles = competitor_json
+        .rank
+        .singles
         .iter()
-        .filter(|pr| pr.eventId == event.code_name())
-        .map(|pr| pr.best)
-        .map(|time| Duration::new(time as u64 / 100, (time % 100) * 10 * 1000 * 1000))
-        .next()
+        .map(|pr| (pr, RecordType::Single));
+    for (pr, record_type) in averages.chain(singles) {
+        let Ok(event) = Event::try_from(pr.eventId.as_str()) else {
+            continue;
+        };
+        let value = ResultValue::from_wca_value(event, pr.best, record_type == RecordType::Average);
+        let ranks = pr.rank.as_ref().map(|rank| Ranks {
+            world: rank.world,
+            continent: rank.continent,
+            country: rank.country,
+        });
+        records
+            .entry(event)
+            .or_default()
+            .set(record_type, value, ranks);
+    }
+    records
+}
+
+fn parse_recent_attempts(results: &serde_json::Value) -> RecentAttempts {
+    let mut competitions: Vec<_> = results.as_object().into_iter().flatten().collect();
+    // the API has no competition dates, but competition IDs end with the year. Competitions of
+    // the same year stay in alphabetical order, so their order is only approximate.
+    competitions.sort_by_key(|(id, _)| {
+        let name = id.trim_end_matches(|c: char| c.is_ascii_digit());
+        std::cmp::Reverse(id[name.len()..].parse::<u32>().unwrap_or_default())
+    });
+
+    let mut attempts = RecentAttempts::new();
+    for (_, events) in competitions {
+        for (event_id, rounds) in events.as_object().into_iter().flatten() {
+            let Ok(event) = Event::try_from(event_id.as_str()) else {
+                continue;
+            };
+            let event_attempts = attempts.entry(event).or_default();
+            // rounds are listed first to final, the final is the most recent
+            for round in rounds.as_array().into_iter().flatten().rev() {
+                let solves = round["solves"].as_array().into_iter().flatten();
+                for value in solves.filter_map(|solve| solve.as_i64()) {
+                    if event_attempts.len() < RECENT_ATTEMPT_COUNT && value != 0 {
+                        event_attempts.push(ResultValue::from_wca_attempt(event, value));
+                    }
+                }
+            }
+        }
+    }
+    attempts
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn time(centiseconds: u64) -> Option<ResultValue> {
+        Some(ResultValue::Time(Duration::from_millis(centiseconds * 10)))
+    }
+
+    #[test]
+    fn orders_attempts_by_competition_year_and_round() {
+        let results = serde_json::json!({
+            "AOpen2019": {"333": [{"solves": [900, 910, 920, 930, 940]}]},
+            "BOpen2023": {"333": [
+                {"solves": [800, -1, 820, 0, 0]},
+                {"solves": [700, 710, 720, 730, 740]}
+            ]},
+            "COpen2021": {"333": [{"solves": [600, 610, 620, 630, 640]}], "magic": []}
+        });
+        let attempts = parse_recent_attempts(&results);
+        let expected: Vec<_> = [700, 710, 720, 730, 740, 800]
+            .into_iter()
+            .map(time)
+            .chain([None, time(820)])
+            .chain([600, 610, 620, 630, 640, 900, 910, 920, 930, 940].map(time))
+            .collect();
+        assert_eq!(attempts[&Event::Ev333], expected);
+        assert_eq!(attempts.len(), 1);
+    }
 }
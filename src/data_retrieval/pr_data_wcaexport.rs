@@ -0,0 +1,615 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use zip::ZipArchive;
+
+use super::{PersonData, PrSource, RecentAttempts, SourceCapabilities, RECENT_ATTEMPT_COUNT};
+use crate::datastructures::{Event, Gender, PersonalRecord, Ranks, RecordType, ResultValue};
+use crate::wcoerror::WCOError;
+
+// file names of the legacy and the current export format
+const RANKS_SINGLE_FILES: [&str; 2] = ["WCA_export_RanksSingle.tsv", "WCA_export_ranks_single.tsv"];
+const RANKS_AVERAGE_FILES: [&str; 2] = [
+    "WCA_export_RanksAverage.tsv",
+    "WCA_export_ranks_average.tsv",
+];
+const PERSONS_FILES: [&str; 2] = ["WCA_export_Persons.tsv", "WCA_export_persons.tsv"];
+const COUNTRIES_FILES: [&str; 2] = ["WCA_export_Countries.tsv", "WCA_export_countries.tsv"];
+const COMPETITIONS_FILES: [&str; 2] =
+    ["WCA_export_Competitions.tsv", "WCA_export_competitions.tsv"];
+const RESULTS_FILES: [&str; 2] = ["WCA_export_Results.tsv", "WCA_export_results.tsv"];
+
+/// Round type IDs of the export from the earliest to the latest round of a competition:
+/// qualification, first, second, semi final, B final and final, with their combined variants.
+const ROUND_TYPE_ORDER: [&str; 11] = ["0", "h", "1", "d", "2", "e", "3", "g", "b", "c", "f"];
+
+/// Answers all lookups from a locally downloaded WCA results export
+/// (https://www.worldcubeassociation.org/export/results), either the zip file or its
+/// extracted directory. The export is loaded into memory once, no network is used.
+pub struct WcaExportSource {
+    records: HashMap<String, HashMap<Event, PersonalRecord>>,
+    persons: HashMap<String, PersonDetails>,
+    recent_attempts: HashMap<String, RecentAttempts>,
+}
+
+#[derive(Clone, Default)]
+struct PersonDetails {
+    country_iso2: Option<String>,
+    gender: Option<Gender>,
+}
+
+impl WcaExportSource {
+    /// Loads the export at `path`. The results table is by far the largest part of the export,
+    /// attempts are only read for the persons in `history_for`.
+    pub fn load(path: &Path, history_for: Option<&HashSet<String>>) -> Result<Self, WCOError> {
+        println!("Loading WCA results export from {:?}...", path);
+        let mut export = ExportFiles::open(path)?;
+        let mut records = HashMap::new();
+        for (record_type, file_names) in [
+            (RecordType::Single, &RANKS_SINGLE_FILES),
+            (RecordType::Average, &RANKS_AVERAGE_FILES),
+        ] {
+            let content = export.read(file_names)?;
+            parse_ranks(&content, record_type, &mut records)?;
+        }
+        let countries = parse_countries(&export.read(&COUNTRIES_FILES)?)?;
+        let persons = parse_persons(&export.read(&PERSONS_FILES)?, &countries)?;
+        let recent_attempts = match history_for {
+            Some(wca_ids) => load_recent_attempts(&mut export, wca_ids).unwrap_or_else(|e| {
+                println!(
+                    "Could not load recent results from the export ({}), using PRs only",
+                    e
+                );
+                HashMap::new()
+            }),
+            None => HashMap::new(),
+        };
+        Ok(WcaExportSource {
+            records,
+            persons,
+            recent_attempts,
+        })
+    }
+}
+
+impl PrSource for WcaExportSource {
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            singles: true,
+            averages: true,
+            ranks: true,
+            synthetic: false,
+        }
+    }
+
+    fn retrieve_records(&self, wca_id: &str) -> Result<PersonData, WCOError> {
+        let details = self.persons.get(wca_id).cloned().unwrap_or_default();
+        Ok(PersonData {
+            records: self.records.get(wca_id).cloned().unwrap_or_default(),
+            country_iso2: details.country_iso2,
+            gender: details.gender,
+            recent_attempts: self
+                .recent_attempts
+                .get(wca_id)
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+}
+
+enum ExportFiles {
+    Directory(PathBuf),
+    Zip(ZipArchive<File>),
+}
+
+impl ExportFiles {
+    fn open(path: &Path) -> Result<Self, WCOError> {
+        match path.is_dir() {
+            true => Ok(ExportFiles::Directory(path.to_owned())),
+            false => Ok(ExportFiles::Zip(ZipArchive::new(File::open(path)?)?)),
+        }
+    }
+
+    /// Reads the first existing file of the given candidate names.
+    fn read(&mut self, file_names: &[&str]) -> Result<String, WCOError> {
+        let mut content = String::new();
+        self.open_file(file_names, |file| {
+            file.read_to_string(&mut content)?;
+            Ok(())
+        })?;
+        Ok(content)
+    }
+
+    /// Passes each line of the first existing file of the given candidate names to `handle_line`
+    /// without loading the whole file into memory.
+    fn for_each_line(
+        &mut self,
+        file_names: &[&str],
+        mut handle_line: impl FnMut(&str) -> Result<(), WCOError>,
+    ) -> Result<(), WCOError> {
+        self.open_file(file_names, |file| {
+            for line in BufReader::new(file).lines() {
+                handle_line(&line?)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn open_file(
+        &mut self,
+        file_names: &[&str],
+        read: impl FnOnce(&mut dyn Read) -> Result<(), WCOError>,
+    ) -> Result<(), WCOError> {
+        for name in file_names {
+            match self {
+                ExportFiles::Directory(dir) => {
+                    let path = dir.join(name);
+                    if path.exists() {
+                        return read(&mut File::open(path)?);
+                    }
+                }
+                ExportFiles::Zip(archive) => {
+                    if let Ok(mut file) = archive.by_name(name) {
+                        return read(&mut file);
+                    }
+                }
+            }
+        }
+        Err(WCOError::ParsingError(format!(
+            "The WCA export does not contain any of the files {}",
+            file_names.join(", ")
+        )))
+    }
+}
+
+fn parse_ranks(
+    content: &str,
+    record_type: RecordType,
+    records: &mut HashMap<String, HashMap<Event, PersonalRecord>>,
+) -> Result<(), WCOError> {
+    let mut lines = content.lines();
+    let header: Vec<_> = lines.next().unwrap_or_default().split('\t').collect();
+    let person_col = column_index(&header, &["personid"])?;
+    let event_col = column_index(&header, &["eventid"])?;
+    let best_col = column_index(&header, &["best"])?;
+    let world_col = column_index(&header, &["worldrank"])?;
+    let continent_col = column_index(&header, &["continentrank"])?;
+    let country_col = column_index(&header, &["countryrank"])?;
+
+    for line in lines {
+        let fields: Vec<_> = line.split('\t').collect();
+        let (Some(person), Some(event), Some(best)) = (
+            fields.get(person_col),
+            fields
+                .get(event_col)
+                .and_then(|id| Event::try_from(*id).ok()),
+            fields
+                .get(best_col)
+                .and_then(|best| best.parse::<u32>().ok()),
+        ) else {
+            continue;
+        };
+        let value = ResultValue::from_wca_value(event, best, record_type == RecordType::Average);
+        let rank = |col: usize| fields.get(col).and_then(|rank| rank.parse::<u32>().ok());
+        let ranks = match (rank(world_col), rank(continent_col), rank(country_col)) {
+            (Some(world), Some(continent), Some(country)) => Some(Ranks {
+                world,
+                continent,
+                country,
+            }),
+            _ => None,
+        };
+        records
+            .entry(person.to_string())
+            .or_default()
+            .entry(event)
+            .or_default()
+            .set(record_type, value, ranks);
+    }
+    Ok(())
+}
+
+/// Maps the export's country IDs (country names) to ISO codes.
+fn parse_countries(content: &str) -> Result<HashMap<String, String>, WCOError> {
+    let mut lines = content.lines();
+    let header: Vec<_> = lines.next().unwrap_or_default().split('\t').collect();
+    let id_col = column_index(&header, &["id"])?;
+    let iso2_col = column_index(&header, &["iso2"])?;
+    Ok(lines
+        .filter_map(|line| {
+            let fields: Vec<_> = line.split('\t').collect();
+            Some((
+                fields.get(id_col)?.to_string(),
+                fields.get(iso2_col)?.to_string(),
+            ))
+        })
+        .collect())
+}
+
+fn parse_persons(
+    content: &str,
+    countries: &HashMap<String, String>,
+) -> Result<HashMap<String, PersonDetails>, WCOError> {
+    let mut lines = content.lines();
+    let header: Vec<_> = lines.next().unwrap_or_default().split('\t').collect();
+    let id_col = column_index(&header, &["id", "wcaid"])?;
+    let subid_col = column_index(&header, &["subid"])?;
+    let country_col = column_index(&header, &["countryid"])?;
+    let gender_col = column_index(&header, &["gender"])?;
+    Ok(lines
+        .filter_map(|line| {
+            let fields: Vec<_> = line.split('\t').collect();
+            // persons who changed their country have several entries, the current one has subid 1
+            if fields.get(subid_col) != Some(&"1") {
+                return None;
+            }
+            let details = PersonDetails {
+                country_iso2: fields
+                    .get(country_col)
+                    .and_then(|country| countries.get(*country))
+                    .cloned(),
+                gender: fields
+                    .get(gender_col)
+                    .and_then(|gender| Gender::try_from(*gender).ok()),
+            };
+            Some((fields.get(id_col)?.to_string(), details))
+        })
+        .collect())
+}
+
+/// Maps competition IDs to their start date as a sortable "YYYY-MM-DD" string.
+fn parse_competition_dates(content: &str) -> Result<HashMap<String, String>, WCOError> {
+    let mut lines = content.lines();
+    let header: Vec<_> = lines.next().unwrap_or_default().split('\t').collect();
+    let id_col = column_index(&header, &["id"])?;
+    // the legacy export has separate date columns, the current one a start date
+    let date_cols = match column_index(&header, &["startdate"]) {
+        Ok(start_date_col) => vec![start_date_col],
+        Err(_) => vec![
+            column_index(&header, &["year"])?,
+            column_index(&header, &["month"])?,
+            column_index(&header, &["day"])?,
+        ],
+    };
+    Ok(lines
+        .filter_map(|line| {
+            let fields: Vec<_> = line.split('\t').collect();
+            let date = date_cols
+                .iter()
+                .map(|col| fields.get(*col).map(|part| format!("{:0>2}", part)))
+                .collect::<Option<Vec<_>>>()?
+                .join("-");
+            Some((fields.get(id_col)?.to_string(), date))
+        })
+        .collect())
+}
+
+struct RoundAttempts {
+    /// Start date of the competition
+    date: String,
+    /// Position of the round type in `ROUND_TYPE_ORDER`, `None` for unknown round types
+    round: Option<usize>,
+    attempts: Vec<Option<ResultValue>>,
+}
+
+fn load_recent_attempts(
+    export: &mut ExportFiles,
+    wca_ids: &HashSet<String>,
+) -> Result<HashMap<String, RecentAttempts>, WCOError> {
+    let competition_dates = parse_competition_dates(&export.read(&COMPETITIONS_FILES)?)?;
+    parse_results(export, wca_ids, &competition_dates)
+}
+
+/// Collects the most recent attempts of the given persons, newest competitions first.
+fn parse_results(
+    export: &mut ExportFiles,
+    wca_ids: &HashSet<String>,
+    competition_dates: &HashMap<String, String>,
+) -> Result<HashMap<String, RecentAttempts>, WCOError> {
+    let mut rounds: HashMap<(String, Event), Vec<RoundAttempts>> = HashMap::new();
+    let mut columns = None;
+    export.for_each_line(&RESULTS_FILES, |line| {
+        let fields: Vec<_> = line.split('\t').collect();
+        let Some(columns) = &columns else {
+            columns = Some(parse_results_header(&fields)?);
+            return Ok(());
+        };
+        let (Some(person), Some(competition), Some(event)) = (
+            fields
+                .get(columns.person)
+                .filter(|id| wca_ids.contains(**id)),
+            fields.get(columns.competition),
+            fields
+                .get(columns.event)
+                .and_then(|id| Event::try_from(*id).ok()),
+        ) else {
+            return Ok(());
+        };
+        let attempts = columns
+            .values
+            .iter()
+            .filter_map(|col| fields.get(*col)?.parse::<i64>().ok())
+            .filter(|value| *value != 0)
+            .map(|value| ResultValue::from_wca_attempt(event, value))
+            .collect();
+        let date = competition_dates
+            .get(*competition)
+            .cloned()
+            .unwrap_or_default();
+        let round = fields
+            .get(columns.round_type)
+            .and_then(|id| ROUND_TYPE_ORDER.iter().position(|round| round == id));
+        rounds
+            .entry((person.to_string(), event))
+            .or_default()
+            .push(RoundAttempts {
+                date,
+                round,
+                attempts,
+            });
+        Ok(())
+    })?;
+
+    let mut recent_attempts: HashMap<String, RecentAttempts> = HashMap::new();
+    for ((person, event), mut round_list) in rounds {
+        round_list.sort_by(|a, b| (&b.date, b.round).cmp(&(&a.date, a.round)));
+        let attempts = round_list
+            .into_iter()
+            .flat_map(|round| round.attempts)
+            .take(RECENT_ATTEMPT_COUNT)
+            .collect();
+        recent_attempts
+            .entry(person)
+            .or_default()
+            .insert(event, attempts);
+    }
+    Ok(recent_attempts)
+}
+
+struct ResultsColumns {
+    person: usize,
+    competition: usize,
+    event: usize,
+    round_type: usize,
+    values: Vec<usize>,
+}
+
+fn parse_results_header(header: &[&str]) -> Result<ResultsColumns, WCOError> {
+    Ok(ResultsColumns {
+        person: column_index(header, &["personid"])?,
+        competition: column_index(header, &["competitionid"])?,
+        event: column_index(header, &["eventid"])?,
+        round_type: column_index(header, &["roundtypeid"])?,
+        values: ["value1", "value2", "value3", "value4", "value5"]
+            .iter()
+            .map(|name| column_index(header, &[name]))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Finds a column by one of its names, ignoring case and underscores, so that both the legacy
+/// ("personId") and the current ("person_id") export headers are understood.
+fn column_index(header: &[&str], names: &[&str]) -> Result<usize, WCOError> {
+    header
+        .iter()
+        .position(|col| {
+            let col = col.trim().replace('_', "");
+            names.iter().any(|name| col.eq_ignore_ascii_case(name))
+        })
+        .ok_or_else(|| {
+            WCOError::ParsingError(format!(
+                "The WCA export is missing the column \"{}\"",
+                names[0]
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Write;
+    use std::time::Duration;
+
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    use super::*;
+
+    /// Both export layouts have a fixture with the same content.
+    const FORMATS: [&str; 2] = ["legacy", "current"];
+
+    fn fixture_dir(format: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/wcaexport")
+            .join(format)
+    }
+
+    fn open_fixture(format: &str) -> ExportFiles {
+        ExportFiles::open(&fixture_dir(format)).unwrap()
+    }
+
+    /// Packs a fixture directory into a zip file like the one offered for download.
+    fn zip_fixture(format: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "wco-export-test-{}-{}.zip",
+            std::process::id(),
+            format
+        ));
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        for entry in fs::read_dir(fixture_dir(format)).unwrap() {
+            let entry = entry.unwrap();
+            zip.start_file(
+                entry.file_name().to_string_lossy(),
+                SimpleFileOptions::default(),
+            )
+            .unwrap();
+            zip.write_all(&fs::read(entry.path()).unwrap()).unwrap();
+        }
+        zip.finish().unwrap();
+        path
+    }
+
+    fn centis(centiseconds: u64) -> ResultValue {
+        ResultValue::Time(Duration::from_millis(centiseconds * 10))
+    }
+
+    fn history_for(wca_ids: &[&str]) -> HashSet<String> {
+        wca_ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_ranks() {
+        for format in FORMATS {
+            let mut export = open_fixture(format);
+            let mut records = HashMap::new();
+            parse_ranks(
+                &export.read(&RANKS_SINGLE_FILES).unwrap(),
+                RecordType::Single,
+                &mut records,
+            )
+            .unwrap();
+            parse_ranks(
+                &export.read(&RANKS_AVERAGE_FILES).unwrap(),
+                RecordType::Average,
+                &mut records,
+            )
+            .unwrap();
+
+            let jane = &records["2015DOEJ01"];
+            assert_eq!(jane[&Event::Ev333].single, Some(centis(754)));
+            assert_eq!(jane[&Event::Ev333].average, Some(centis(912)));
+            assert_eq!(
+                jane[&Event::Ev333].single_ranks,
+                Some(Ranks {
+                    world: 1200,
+                    continent: 600,
+                    country: 50
+                })
+            );
+            assert_eq!(jane[&Event::Ev333fm].single, Some(ResultValue::Moves(2800)));
+            assert_eq!(
+                jane[&Event::Ev333fm].average,
+                Some(ResultValue::Moves(3133))
+            );
+            // unknown events are skipped
+            let max = &records["2019ROEM01"];
+            assert_eq!(
+                max.keys().collect::<Vec<_>>(),
+                [&Event::Ev333],
+                "{}",
+                format
+            );
+            assert_eq!(max[&Event::Ev333].average, None);
+        }
+    }
+
+    #[test]
+    fn parses_persons_with_their_current_country() {
+        for format in FORMATS {
+            let mut export = open_fixture(format);
+            let countries = parse_countries(&export.read(&COUNTRIES_FILES).unwrap()).unwrap();
+            let persons = parse_persons(&export.read(&PERSONS_FILES).unwrap(), &countries).unwrap();
+            assert_eq!(persons.len(), 2, "{}", format);
+            // Jane used to compete for France, the entry with subid 1 is the current one
+            let jane = &persons["2015DOEJ01"];
+            assert_eq!(jane.country_iso2.as_deref(), Some("DE"), "{}", format);
+            assert_eq!(jane.gender, Some(Gender::Female));
+            let max = &persons["2019ROEM01"];
+            assert_eq!(max.country_iso2.as_deref(), Some("GB"));
+            assert_eq!(max.gender, Some(Gender::Male));
+        }
+    }
+
+    #[test]
+    fn parses_competition_dates_in_both_header_formats() {
+        for format in FORMATS {
+            let dates =
+                parse_competition_dates(&open_fixture(format).read(&COMPETITIONS_FILES).unwrap())
+                    .unwrap();
+            assert_eq!(dates.len(), 2);
+            assert_eq!(dates["Spring2023"], "2023-04-01", "{}", format);
+            assert_eq!(dates["Autumn2023"], "2023-10-14", "{}", format);
+        }
+    }
+
+    #[test]
+    fn orders_recent_attempts_by_date_and_round_type() {
+        for format in FORMATS {
+            let mut export = open_fixture(format);
+            let dates =
+                parse_competition_dates(&export.read(&COMPETITIONS_FILES).unwrap()).unwrap();
+            let attempts =
+                parse_results(&mut export, &history_for(&["2015DOEJ01"]), &dates).unwrap();
+            // only the requested persons are kept
+            assert_eq!(attempts.keys().collect::<Vec<_>>(), ["2015DOEJ01"]);
+
+            let jane = &attempts["2015DOEJ01"];
+            let expected: Vec<_> = [
+                // Autumn2023 final, listed first in the export
+                [800, 850, 0, 900, 780],
+                // Autumn2023 first round
+                [1000, 950, 990, 1010, 1020],
+                // Spring2023 first round
+                [1100, 1150, 1180, 1200, 0],
+            ]
+            .iter()
+            .flatten()
+            .map(|value| (*value > 0).then(|| centis(*value)))
+            .collect();
+            assert_eq!(jane[&Event::Ev333], expected, "{}", format);
+            // unused attempts of a cut off round are left out
+            assert_eq!(
+                jane[&Event::Ev333fm],
+                [
+                    Some(ResultValue::Moves(3000)),
+                    Some(ResultValue::Moves(2800))
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_results_without_round_types() {
+        let dir = std::env::temp_dir().join(format!("wco-export-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let results = fs::read_to_string(fixture_dir("current").join(RESULTS_FILES[1])).unwrap();
+        fs::write(
+            dir.join(RESULTS_FILES[1]),
+            results.replacen("round_type_id", "round", 1),
+        )
+        .unwrap();
+        let result = parse_results(
+            &mut ExportFiles::open(&dir).unwrap(),
+            &history_for(&["2015DOEJ01"]),
+            &HashMap::new(),
+        );
+        let _ = fs::remove_dir_all(&dir);
+        assert!(matches!(result, Err(WCOError::ParsingError(_))));
+    }
+
+    #[test]
+    fn loads_zipped_and_extracted_exports() {
+        for format in FORMATS {
+            let zip = zip_fixture(format);
+            let from_zip = WcaExportSource::load(&zip, Some(&history_for(&["2015DOEJ01"])));
+            let _ = fs::remove_file(&zip);
+            let from_dir =
+                WcaExportSource::load(&fixture_dir(format), Some(&history_for(&["2015DOEJ01"])))
+                    .unwrap();
+            let from_zip = from_zip.unwrap();
+
+            for source in [from_dir, from_zip] {
+                let jane = source.retrieve_records("2015DOEJ01").unwrap();
+                assert_eq!(jane.records[&Event::Ev333].single, Some(centis(754)));
+                assert_eq!(jane.country_iso2.as_deref(), Some("DE"));
+                assert_eq!(jane.recent_attempts[&Event::Ev333].len(), 15);
+                // records are loaded for everyone, attempts only for the requested persons
+                let max = source.retrieve_records("2019ROEM01").unwrap();
+                assert_eq!(max.records[&Event::Ev333].single, Some(centis(1032)));
+                assert!(max.recent_attempts.is_empty());
+            }
+        }
+    }
+}
@@ -1,41 +1,143 @@
-use crate::datastructures::{Competitor, Event};
+use super::fetcher::Fetcher;
+use super::{PersonData, PrSource, SourceCapabilities, SourceConfig};
+use crate::datastructures::{Event, PersonalRecord, Ranks, RecordType, ResultValue};
 use crate::wcoerror::WCOError;
-use reqwest::blocking::get;
-use scraper::{Html, Selector};
-use std::{ops::Add, time::Duration};
+use scraper::{ElementRef, Html, Selector};
+use std::{collections::HashMap, ops::Add, sync::Arc, time::Duration};
 
-pub fn retrieve_competitor_pr_avg_html(
-    competitor: &mut Competitor,
-    event: Event,
-) -> Result<(), WCOError> {
-    if let Some(id) = &mut competitor.wca_id {
-        let url = format!("https://www.worldcubeassociation.org/persons/{}", id);
-        let html = Html::parse_document(&get(url)?.text()?);
-        if let Some(avg) = parse_pr_html(&html, event)? {
-            competitor.personal_records.insert(Event::Ev333, avg);
+pub struct WcaWebsiteSource {
+    fetcher: Arc<Fetcher>,
+    base_url: String,
+}
+
+impl WcaWebsiteSource {
+    pub fn new(config: &SourceConfig) -> Self {
+        WcaWebsiteSource {
+            fetcher: Arc::clone(&config.fetcher),
+            base_url: config.wca_url.trim_end_matches('/').to_owned(),
         }
     }
-    Ok(())
 }
 
-fn parse_pr_html(competitor_html: &Html, event: Event) -> Result<Option<Duration>, WCOError> {
-    let selector = Selector::parse(&format!(
-        r#"a[href="/results/rankings/{}/{}"]"#,
-        event.code_name(),
-        match event.use_average() {
-            true => "average",
-            false => "single",
+impl PrSource for WcaWebsiteSource {
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            singles: true,
+            averages: true,
+            ranks: true,
+            synthetic: false,
+        }
+    }
+
+    fn retrieve_records(&self, wca_id: &str) -> Result<PersonData, WCOError> {
+        let url = format!("{}/persons/{}", self.base_url, wca_id);
+        let html = Html::parse_document(&self.fetcher.get_text(&url)?);
+        Ok(PersonData {
+            records: parse_pr_html(&html)?,
+            ..PersonData::default()
+        })
+    }
+}
+
+fn parse_pr_html(competitor_html: &Html) -> Result<HashMap<Event, PersonalRecord>, WCOError> {
+    let selector = Selector::parse(r#"a[href^="/results/rankings/"]"#)
+        .expect("Parsing known selector should not fail");
+
+    let mut records: HashMap<Event, PersonalRecord> = HashMap::new();
+    for element in competitor_html.select(&selector) {
+        let Some((event, record_type)) = element.value().attr("href").and_then(parse_rankings_link)
+        else {
+            continue;
+        };
+        let record = records.entry(event).or_default();
+        if record.get(record_type).is_some() {
+            continue;
         }
-    ))
-    .expect("Parsing known selector should not fail");
+        let result_str = element.text().collect::<String>().trim().to_owned();
+        let value = match event {
+            Event::Ev333fm => ResultValue::Moves(parse_moves(&result_str)?),
+            Event::Ev333mbf => parse_multiblind(&result_str)?,
+            _ => ResultValue::Time(parse_time(&result_str)?),
+        };
+        record.set(record_type, value, parse_row_ranks(element, record_type));
+    }
+    Ok(records)
+}
+
+/// Reads the ranks from the table row of a record link. Each row of the personal records table
+/// contains the rank cells twice: the single's ranks first, followed by the average's.
+fn parse_row_ranks(link: ElementRef, record_type: RecordType) -> Option<Ranks> {
+    let row = link
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|elem| elem.value().name() == "tr")?;
+    let occurrence = match record_type {
+        RecordType::Single => 0,
+        RecordType::Average => 1,
+    };
+    let rank = |class: &str| {
+        let selector = Selector::parse(&format!("td.{}", class))
+            .expect("Parsing known selector should not fail");
+        row.select(&selector)
+            .nth(occurrence)
+            .and_then(|cell| cell.text().collect::<String>().trim().parse::<u32>().ok())
+    };
+    Some(Ranks {
+        world: rank("world-rank")?,
+        continent: rank("continent-rank")?,
+        country: rank("country-rank")?,
+    })
+}
+
+/// Parses a "/results/rankings/{event}/{single|average}" link.
+fn parse_rankings_link(href: &str) -> Option<(Event, RecordType)> {
+    let mut parts = href.trim_start_matches("/results/rankings/").split('/');
+    let event = parts.next().and_then(|code| Event::try_from(code).ok())?;
+    match parts.next() {
+        Some("single") => Some((event, RecordType::Single)),
+        Some("average") => Some((event, RecordType::Average)),
+        _ => None,
+    }
+}
 
-    match competitor_html
-        .select(&selector)
-        .next()
-        .map(|element| element.text().collect::<String>().trim().to_owned())
-    {
-        Some(time_str) => Ok(Some(parse_time(&time_str)?)),
-        None => Ok(None),
+/// Parses a move count like "25" (single) or "28.33" (average) into hundredths of moves.
+fn parse_moves(text: &str) -> Result<u32, WCOError> {
+    let msg = format!("Cannot parse move count from string \"{}\"", text);
+    let (moves, decimals) = text.split_once('.').unwrap_or((text, "00"));
+    // "28.3" are 28.30 moves
+    let hundredths = match decimals.len() {
+        1 => decimals.parse::<u32>().map(|tenths| tenths * 10),
+        2 => decimals.parse::<u32>(),
+        _ => return Err(WCOError::ParsingError(msg)),
+    };
+    match (moves.parse::<u32>(), hundredths) {
+        (Ok(moves), Ok(hundredths)) => Ok(moves * 100 + hundredths),
+        _ => Err(WCOError::ParsingError(msg)),
+    }
+}
+
+/// Parses a multi-blind result like "9/10 58:12" (solved/attempted, time in [h:]mm:ss).
+fn parse_multiblind(text: &str) -> Result<ResultValue, WCOError> {
+    let msg = format!("Cannot parse multi-blind result from string \"{}\"", text);
+    let (cubes, time) = text
+        .split_once(' ')
+        .ok_or_else(|| WCOError::ParsingError(msg.clone()))?;
+    let (solved, attempted) = cubes
+        .split_once('/')
+        .ok_or_else(|| WCOError::ParsingError(msg.clone()))?;
+    let seconds = time
+        .trim()
+        .split(':')
+        .try_fold(0u64, |acc, part| part.parse::<u64>().map(|v| acc * 60 + v));
+    match (solved.parse::<u32>(), attempted.parse::<u32>(), seconds) {
+        (Ok(solved), Ok(attempted), Ok(seconds)) if solved <= attempted => {
+            Ok(ResultValue::MultiBlind {
+                solved,
+                attempted,
+                time: Duration::from_secs(seconds),
+            })
+        }
+        _ => Err(WCOError::ParsingError(msg)),
     }
 }
 
@@ -69,3 +171,37 @@ fn parse_sub_minute_time(text: &str) -> Result<Duration, WCOError> {
         _ => Err(WCOError::ParsingError(msg)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_move_counts() {
+        assert_eq!(parse_moves("25").unwrap(), 2500);
+        assert_eq!(parse_moves("28.33").unwrap(), 2833);
+        assert_eq!(parse_moves("28.3").unwrap(), 2830);
+        assert!(parse_moves("DNF").is_err());
+        assert!(parse_moves("DNS").is_err());
+        assert!(parse_moves("28.333").is_err());
+        assert!(parse_moves("").is_err());
+    }
+
+    #[test]
+    fn parses_multiblind_results() {
+        let result = |solved, attempted, seconds| ResultValue::MultiBlind {
+            solved,
+            attempted,
+            time: Duration::from_secs(seconds),
+        };
+        assert_eq!(parse_multiblind("9/10 58:12").unwrap(), result(9, 10, 3492));
+        assert_eq!(parse_multiblind("2/2 9:05").unwrap(), result(2, 2, 545));
+        assert_eq!(
+            parse_multiblind("40/45 1:00:00").unwrap(),
+            result(40, 45, 3600)
+        );
+        assert!(parse_multiblind("11/10 58:12").is_err());
+        assert!(parse_multiblind("9/10").is_err());
+        assert!(parse_multiblind("DNF").is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::wcoerror::WCOError;
+
+use super::pr_data_random::RandomSource;
+use super::pr_data_unofficialapi::UnofficialApiSource;
+use super::pr_data_wcaexport::WcaExportSource;
+use super::pr_data_wcawebsite::WcaWebsiteSource;
+use super::{PrSource, SourceConfig};
+
+pub type SourceFactory = fn(&SourceConfig) -> Result<Box<dyn PrSource>, WCOError>;
+
+pub struct SourceRegistry {
+    factories: Vec<(&'static str, SourceFactory)>,
+}
+
+impl SourceRegistry {
+    pub fn empty() -> Self {
+        SourceRegistry { factories: vec![] }
+    }
+
+    /// Makes a source available under the given name. A later registration with the same
+    /// name replaces the earlier one.
+    pub fn register(&mut self, name: &'static str, factory: SourceFactory) {
+        self.factories
+            .retain(|(known, _)| !known.eq_ignore_ascii_case(name));
+        self.factories.push((name, factory));
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.factories.iter().map(|(name, _)| *name).collect()
+    }
+
+    pub fn create(&self, name: &str, config: &SourceConfig) -> Result<Box<dyn PrSource>, WCOError> {
+        self.factories
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            .map(|(_, factory)| factory(config))
+            .unwrap_or_else(|| {
+                Err(WCOError::ParsingError(format!(
+                    "Invalid source specified: \"{}\" (available: {})",
+                    name,
+                    self.names().join(", ")
+                )))
+            })
+    }
+}
+
+impl Default for SourceRegistry {
+    fn default() -> Self {
+        let mut registry = SourceRegistry::empty();
+        registry.register("UnofficialAPI", |config| {
+            Ok(Box::new(UnofficialApiSource::new(config)))
+        });
+        registry.register("WCAwebsite", |config| {
+            Ok(Box::new(WcaWebsiteSource::new(config)))
+        });
+        registry.register("WCAexport", |config| {
+            let path = config.export_path.as_ref().ok_or_else(|| {
+                WCOError::ParsingError("The WCAexport source requires --export-path".to_owned())
+            })?;
+            Ok(Box::new(WcaExportSource::load(
+                path,
+                config.history_for.as_ref(),
+            )?))
+        });
+        registry.register("Debug", |_| Ok(Box::new(RandomSource)));
+        registry
+    }
+}
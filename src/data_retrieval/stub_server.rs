@@ -0,0 +1,90 @@
+//! Minimal HTTP server for tests of the retrieval code.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+/// A request the stub server received.
+pub struct StubRequest {
+    pub path: String,
+    pub received: Instant,
+}
+
+/// Answers every request with the status and body returned by the responder, which is given the
+/// number of earlier requests and the requested path. The server runs until the test ends.
+pub struct StubServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<StubRequest>>>,
+}
+
+impl StubServer {
+    pub fn start<F>(responder: F) -> StubServer
+    where
+        F: Fn(usize, &str) -> (u16, String) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("could not bind stub server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let responder = Arc::new(responder);
+        let server_requests = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (requests, responder) = (Arc::clone(&server_requests), Arc::clone(&responder));
+                thread::spawn(move || {
+                    let mut reader = BufReader::new(&stream);
+                    let mut request_line = String::new();
+                    if reader.read_line(&mut request_line).is_err() {
+                        return;
+                    }
+                    // skip the headers, the stub does not need them
+                    let mut header = String::new();
+                    while reader.read_line(&mut header).is_ok_and(|len| len > 2) {
+                        header.clear();
+                    }
+                    let path = request_line
+                        .split_whitespace()
+                        .nth(1)
+                        .unwrap_or_default()
+                        .to_owned();
+                    let index = {
+                        let mut requests = requests.lock().unwrap();
+                        requests.push(StubRequest {
+                            path: path.clone(),
+                            received: Instant::now(),
+                        });
+                        requests.len() - 1
+                    };
+                    let (status, body) = responder(index, &path);
+                    let _ = write!(
+                        &stream,
+                        "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                });
+            }
+        });
+        StubServer { url, requests }
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// Times at which the requests arrived, in order of arrival.
+    pub fn arrival_times(&self) -> Vec<Instant> {
+        let requests = self.requests.lock().unwrap();
+        requests.iter().map(|request| request.received).collect()
+    }
+
+    pub fn paths(&self) -> Vec<String> {
+        let requests = self.requests.lock().unwrap();
+        requests
+            .iter()
+            .map(|request| request.path.clone())
+            .collect()
+    }
+}
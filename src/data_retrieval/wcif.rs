@@ -0,0 +1,428 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use regex::Regex;
+use serde::Deserialize;
+
+use super::fetcher::Fetcher;
+use crate::datastructures::{
+    AdvancementCondition, Competition, CompetitionEvent, Competitor, Cutoff, Event, Gender,
+    ResultValue, Round, RoundFormat, ScheduledRound, TimeLimit,
+};
+use crate::wcoerror::WCOError;
+
+#[derive(Deserialize)]
+struct Wcif {
+    name: String,
+    persons: Vec<WcifPerson>,
+    events: Vec<WcifEvent>,
+    schedule: Option<WcifSchedule>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+struct WcifPerson {
+    registrantId: Option<u64>,
+    name: String,
+    wcaId: Option<String>,
+    countryIso2: Option<String>,
+    gender: Option<String>,
+    registration: Option<WcifRegistration>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+struct WcifRegistration {
+    eventIds: Vec<String>,
+    status: String,
+    #[serde(default = "default_is_competing")]
+    isCompeting: bool,
+}
+
+fn default_is_competing() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct WcifEvent {
+    id: String,
+    rounds: Vec<WcifRound>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+struct WcifRound {
+    format: String,
+    advancementCondition: Option<WcifAdvancementCondition>,
+    cutoff: Option<WcifCutoff>,
+    timeLimit: Option<WcifTimeLimit>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+struct WcifCutoff {
+    numberOfAttempts: u32,
+    attemptResult: u32,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+struct WcifTimeLimit {
+    centiseconds: u64,
+    #[serde(default)]
+    cumulativeRoundIds: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct WcifAdvancementCondition {
+    #[serde(rename = "type")]
+    condition_type: String,
+    level: u32,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+struct WcifSchedule {
+    startDate: String,
+    numberOfDays: u64,
+    #[serde(default)]
+    venues: Vec<WcifVenue>,
+}
+
+#[derive(Deserialize)]
+struct WcifVenue {
+    timezone: Option<String>,
+    #[serde(default)]
+    rooms: Vec<WcifRoom>,
+}
+
+#[derive(Deserialize)]
+struct WcifRoom {
+    name: String,
+    #[serde(default)]
+    activities: Vec<WcifActivity>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+struct WcifActivity {
+    id: u64,
+    activityCode: String,
+    startTime: String,
+    endTime: String,
+    #[serde(default)]
+    childActivities: Vec<WcifActivity>,
+}
+
+/// Loads competition details and all accepted competitors from the public WCIF of a competition.
+/// Fails only if the WCIF cannot be downloaded or is not valid WCIF JSON.
+pub fn retrieve_competition(
+    fetcher: &Fetcher,
+    wca_url: &str,
+    competition_id: &str,
+) -> Result<(Competition, Vec<Competitor>), WCOError> {
+    let url = format!(
+        "{}/api/v0/competitions/{}/wcif/public",
+        wca_url.trim_end_matches('/'),
+        competition_id
+    );
+    parse_wcif(&fetcher.get_text(&url)?)
+}
+
+/// Parses a WCIF document. Only documents that are not valid WCIF JSON are rejected, details
+/// this tool does not understand are left out: unknown events are skipped, events with a round
+/// of an unknown format or advancement condition get no rounds and without a (valid) schedule,
+/// the dates and scheduled rounds are unknown.
+fn parse_wcif(json: &str) -> Result<(Competition, Vec<Competitor>), WCOError> {
+    let wcif: Wcif = serde_json::from_str(json)?;
+
+    let start_date = wcif
+        .schedule
+        .as_ref()
+        .and_then(|schedule| NaiveDate::parse_from_str(&schedule.startDate, "%Y-%m-%d").ok());
+    let end_date = start_date
+        .zip(wcif.schedule.as_ref())
+        .map(|(start, schedule)| {
+            start
+                .checked_add_days(Days::new(schedule.numberOfDays.saturating_sub(1)))
+                .unwrap_or(start)
+        });
+
+    let events = wcif
+        .events
+        .iter()
+        .filter_map(|ev| {
+            Event::try_from(ev.id.as_str())
+                .ok()
+                .map(|event| (event, ev))
+        })
+        .map(|(event, ev)| CompetitionEvent {
+            event,
+            rounds: ev
+                .rounds
+                .iter()
+                .map(|round| parse_round(event, round))
+                .collect::<Option<_>>()
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    let competitors = wcif
+        .persons
+        .into_iter()
+        .filter_map(|person| {
+            let registration = person.registration?;
+            if registration.status != "accepted" || !registration.isCompeting {
+                return None;
+            }
+            let events = registration
+                .eventIds
+                .iter()
+                .filter_map(|id| Event::try_from(id.as_str()).ok())
+                .collect();
+            let mut competitor = Competitor::new(person.name, person.wcaId, events);
+            competitor.country_iso2 = person.countryIso2;
+            competitor.registrant_id = person.registrantId;
+            competitor.gender = person
+                .gender
+                .as_deref()
+                .and_then(|gender| Gender::try_from(gender).ok());
+            Some(competitor)
+        })
+        .collect();
+
+    let mut schedule: Vec<_> = wcif
+        .schedule
+        .iter()
+        .flat_map(|schedule| &schedule.venues)
+        .flat_map(|venue| {
+            let timezone = venue
+                .timezone
+                .as_deref()
+                .and_then(|timezone| timezone.parse::<Tz>().ok());
+            venue.rooms.iter().map(move |room| (room, timezone))
+        })
+        .flat_map(|(room, timezone)| {
+            room.activities
+                .iter()
+                .filter_map(move |activity| parse_scheduled_round(activity, &room.name, timezone))
+        })
+        .collect();
+    schedule.sort_by_key(|round| round.start);
+
+    let competition = Competition {
+        name: wcif.name,
+        start_date,
+        end_date,
+        events,
+        schedule,
+    };
+    Ok((competition, competitors))
+}
+
+/// Parses activity codes like "333-r1" or "333fm-r1-a2", other activities (lunch, awards) are
+/// skipped.
+fn parse_scheduled_round(
+    activity: &WcifActivity,
+    room: &str,
+    timezone: Option<Tz>,
+) -> Option<ScheduledRound> {
+    let round_re = Regex::new(r"^([a-z0-9]+)-r(\d+)(?:-a(\d+))?$").unwrap();
+    let cap = round_re.captures(&activity.activityCode)?;
+    let group_re = Regex::new(r"-g\d+$").unwrap();
+    let parse_time = |time: &str| {
+        DateTime::parse_from_rfc3339(time)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
+    };
+    Some(ScheduledRound {
+        activity_id: activity.id,
+        event: Event::try_from(&cap[1]).ok()?,
+        round: cap[2].parse().ok()?,
+        attempt: cap.get(3).and_then(|attempt| attempt.as_str().parse().ok()),
+        room: room.to_owned(),
+        start: parse_time(&activity.startTime)?,
+        end: parse_time(&activity.endTime)?,
+        timezone,
+        group_activity_ids: activity
+            .childActivities
+            .iter()
+            .filter(|child| group_re.is_match(&child.activityCode))
+            .map(|child| child.id)
+            .collect(),
+    })
+}
+
+/// Returns `None` if the round format or advancement condition is unknown.
+fn parse_round(event: Event, round: &WcifRound) -> Option<Round> {
+    let advancement = match &round.advancementCondition {
+        Some(condition) => Some(parse_advancement_condition(condition)?),
+        None => None,
+    };
+    Some(Round {
+        format: RoundFormat::try_from(round.format.as_str()).ok()?,
+        advancement,
+        cutoff: round.cutoff.as_ref().map(|cutoff| Cutoff {
+            attempts: cutoff.numberOfAttempts,
+            result: ResultValue::from_wca_value(event, cutoff.attemptResult, false),
+        }),
+        time_limit: round.timeLimit.as_ref().map(|limit| TimeLimit {
+            time: Duration::from_millis(limit.centiseconds * 10),
+            cumulative: !limit.cumulativeRoundIds.is_empty(),
+        }),
+    })
+}
+
+fn parse_advancement_condition(
+    condition: &WcifAdvancementCondition,
+) -> Option<AdvancementCondition> {
+    match condition.condition_type.as_str() {
+        "ranking" => Some(AdvancementCondition::Ranking(condition.level)),
+        "percent" => Some(AdvancementCondition::Percent(condition.level)),
+        "attemptResult" => Some(AdvancementCondition::AttemptResult(condition.level)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_fixture(name: &str) -> (Competition, Vec<Competitor>) {
+        let path = format!(
+            "{}/tests/fixtures/wcif/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        parse_wcif(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn time(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_accepted_competing_registrations() {
+        let (competition, competitors) = parse_fixture("full.json");
+        assert_eq!(competition.name, "Fixture Open 2024");
+        let names: Vec<_> = competitors.iter().map(|comp| comp.name.as_str()).collect();
+        assert_eq!(names, ["Alice Example", "Bob Newcomer", "Frank Oldevent"]);
+
+        let alice = &competitors[0];
+        assert_eq!(alice.wca_id.as_deref(), Some("2015EXAM01"));
+        assert_eq!(alice.events, [Event::Ev333, Event::Ev222]);
+        assert_eq!(alice.country_iso2.as_deref(), Some("DE"));
+        assert_eq!(alice.gender, Some(Gender::Female));
+        assert_eq!(alice.registrant_id, Some(1));
+        assert_eq!(competitors[1].wca_id, None);
+        // unknown events are dropped from registrations
+        assert_eq!(competitors[2].events, [Event::Ev333]);
+    }
+
+    #[test]
+    fn parses_rounds_with_limits_and_advancement() {
+        let (competition, _) = parse_fixture("full.json");
+        let events: Vec<_> = competition.events.iter().map(|ev| ev.event).collect();
+        assert_eq!(events, [Event::Ev333, Event::Ev222, Event::Ev333fm]);
+
+        let cube = &competition.event(&Event::Ev333).unwrap().rounds;
+        assert_eq!(cube.len(), 2);
+        assert_eq!(cube[0].format, RoundFormat::AverageOf5);
+        assert_eq!(cube[0].advancement, Some(AdvancementCondition::Ranking(16)));
+        assert_eq!(
+            cube[0].cutoff,
+            Some(Cutoff {
+                attempts: 2,
+                result: ResultValue::Time(Duration::from_secs(30)),
+            })
+        );
+        assert_eq!(
+            cube[0].time_limit,
+            Some(TimeLimit {
+                time: Duration::from_secs(600),
+                cumulative: false,
+            })
+        );
+        assert_eq!(cube[1].advancement, None);
+        assert_eq!(cube[1].cutoff, None);
+
+        let two = &competition.event(&Event::Ev222).unwrap().rounds;
+        assert_eq!(two[0].advancement, Some(AdvancementCondition::Percent(75)));
+        assert!(two[0].time_limit.unwrap().cumulative);
+
+        let fewest_moves = &competition.event(&Event::Ev333fm).unwrap().rounds;
+        assert_eq!(fewest_moves[0].format, RoundFormat::MeanOf3);
+        assert_eq!(
+            fewest_moves[0].advancement,
+            Some(AdvancementCondition::AttemptResult(40))
+        );
+        assert_eq!(fewest_moves[0].time_limit, None);
+        assert_eq!(fewest_moves[1].format, RoundFormat::BestOf(1));
+    }
+
+    #[test]
+    fn parses_scheduled_rounds_and_groups() {
+        let (competition, _) = parse_fixture("full.json");
+        assert_eq!(competition.start_date, NaiveDate::from_ymd_opt(2024, 5, 11));
+        assert_eq!(competition.end_date, NaiveDate::from_ymd_opt(2024, 5, 12));
+
+        // ordered by start time across rooms, other activities are skipped
+        let schedule = &competition.schedule;
+        let ids: Vec<_> = schedule.iter().map(|round| round.activity_id).collect();
+        assert_eq!(ids, [4, 2, 5, 3]);
+
+        let fewest_moves = &schedule[0];
+        assert_eq!(fewest_moves.event, Event::Ev333fm);
+        assert_eq!(fewest_moves.round, 1);
+        assert_eq!(fewest_moves.attempt, Some(1));
+        assert_eq!(fewest_moves.room, "Side Room");
+
+        let cube = &schedule[1];
+        assert_eq!(
+            (cube.event, cube.round, cube.attempt),
+            (Event::Ev333, 1, None)
+        );
+        assert_eq!(cube.room, "Main Stage");
+        assert_eq!(cube.start, time("2024-05-11T07:00:00Z"));
+        assert_eq!(cube.end, time("2024-05-11T08:30:00Z"));
+        assert_eq!(cube.group_activity_ids, [21, 22]);
+        assert_eq!(cube.timezone, Some(chrono_tz::Europe::Berlin));
+        assert!(schedule[3].group_activity_ids.is_empty());
+    }
+
+    #[test]
+    fn leaves_out_rounds_of_events_with_unknown_round_details() {
+        let (competition, competitors) = parse_fixture("unknown_rounds.json");
+        assert_eq!(competitors.len(), 1);
+        let rounds = |event| competition.event(&event).unwrap().rounds.len();
+        // unknown round format
+        assert_eq!(rounds(Event::Ev333), 0);
+        // unknown advancement condition
+        assert_eq!(rounds(Event::Ev222), 0);
+        assert_eq!(rounds(Event::Ev444), 1);
+        // unknown time zone
+        assert_eq!(competition.schedule.len(), 1);
+        assert_eq!(competition.schedule[0].timezone, None);
+    }
+
+    #[test]
+    fn parses_competitions_without_schedule() {
+        let (competition, competitors) = parse_fixture("no_schedule.json");
+        assert_eq!(competition.start_date, None);
+        assert_eq!(competition.end_date, None);
+        assert!(competition.schedule.is_empty());
+        assert_eq!(competition.event(&Event::Ev333).unwrap().rounds.len(), 1);
+        assert_eq!(competitors.len(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        assert!(matches!(
+            parse_wcif(r#"{"name": "Broken", "persons": []}"#),
+            Err(WCOError::JsonError(_))
+        ));
+    }
+}
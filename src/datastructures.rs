@@ -1,13 +1,234 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+    time::Duration,
+};
 
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use regex::Regex;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::countries::{country_by_iso2, Country};
 use crate::wcoerror::WCOError;
 
-#[derive(Debug)]
+/// Canonical WCA competition ID like "HessenMiniOpen2024".
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct CompetitionId(String);
+
+impl CompetitionId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn registrations_url(&self, wca_url: &str) -> String {
+        format!(
+            "{}/competitions/{}/registrations",
+            wca_url.trim_end_matches('/'),
+            self.0
+        )
+    }
+}
+
+/// Accepts a bare competition ID or any URL containing "/competitions/<ID>", like the
+/// competition's main page, registrations page or psych sheet.
+impl FromStr for CompetitionId {
+    type Err = WCOError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim();
+        let id_re = Regex::new(r"^[A-Za-z0-9]+$").unwrap();
+        if id_re.is_match(input) {
+            return Ok(CompetitionId(input.to_owned()));
+        }
+        let url_re = Regex::new(r"(?:^|/)competitions/([A-Za-z0-9]+)(?:[/?#]|$)").unwrap();
+        url_re
+            .captures(input)
+            .map(|cap| CompetitionId(cap[1].to_owned()))
+            .ok_or_else(|| {
+                WCOError::ParsingError(format!(
+                    "\"{}\" is neither a competition ID nor a competition URL",
+                    s
+                ))
+            })
+    }
+}
+
+impl fmt::Display for CompetitionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Competition {
+    pub name: String,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    /// Empty if the competition was loaded from the registrations page, which lacks event details
+    pub events: Vec<CompetitionEvent>,
+    /// Scheduled rounds ordered by start time, empty if the schedule is unknown
+    pub schedule: Vec<ScheduledRound>,
+}
+
+impl Competition {
+    pub fn event(&self, event: &Event) -> Option<&CompetitionEvent> {
+        self.events.iter().find(|ev| ev.event == *event)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompetitionEvent {
+    pub event: Event,
+    pub rounds: Vec<Round>,
+}
+
+/// Time slot of a round in the competition's schedule.
+#[derive(Debug, Serialize)]
+pub struct ScheduledRound {
+    /// WCIF activity ID
+    pub activity_id: u64,
+    pub event: Event,
+    /// Round number, starting at 1
+    pub round: usize,
+    /// Attempt number, for events whose attempts are scheduled separately (Fewest Moves, Multi-Blind)
+    pub attempt: Option<usize>,
+    pub room: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Time zone of the venue, `None` if the WCIF does not name a known one
+    pub timezone: Option<Tz>,
+    /// WCIF activity IDs of the groups the round is split into, empty if the schedule does not
+    /// define groups
+    pub group_activity_ids: Vec<u64>,
+}
+
+impl ScheduledRound {
+    pub fn duration(&self) -> Duration {
+        (self.end - self.start).to_std().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Round {
+    pub format: RoundFormat,
+    /// Condition to advance from this round to the next one, `None` for final rounds
+    pub advancement: Option<AdvancementCondition>,
+    pub cutoff: Option<Cutoff>,
+    /// `None` for events without time limit (Fewest Moves, Multi-Blind)
+    pub time_limit: Option<TimeLimit>,
+}
+
+/// Only competitors with an attempt better than `result` in the first `attempts` attempts
+/// continue with the remaining attempts of the round.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
+pub struct Cutoff {
+    pub attempts: u32,
+    pub result: ResultValue,
+}
+
+/// Attempts slower than the time limit are DNF. A cumulative limit applies to the sum of all
+/// attempts, possibly across several rounds.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
+pub struct TimeLimit {
+    #[serde(rename = "centiseconds", serialize_with = "serialize_centiseconds")]
+    pub time: Duration,
+    pub cumulative: bool,
+}
+
+fn serialize_centiseconds<S: Serializer>(
+    time: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(time.as_millis() as u64 / 10)
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
+#[serde(tag = "type", content = "level", rename_all = "snake_case")]
+pub enum AdvancementCondition {
+    /// The best n competitors advance
+    Ranking(u32),
+    /// The best n percent of competitors advance
+    Percent(u32),
+    /// Competitors with a result better than the given WCA encoded value advance
+    AttemptResult(u32),
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum RoundFormat {
+    BestOf(u32),
+    MeanOf3,
+    AverageOf5,
+}
+
+impl RoundFormat {
+    pub fn pretty_name(&self) -> String {
+        match self {
+            RoundFormat::BestOf(n) => format!("Best of {}", n),
+            RoundFormat::MeanOf3 => "Mean of 3".to_string(),
+            RoundFormat::AverageOf5 => "Average of 5".to_string(),
+        }
+    }
+
+    pub fn attempt_count(&self) -> usize {
+        match self {
+            RoundFormat::BestOf(n) => *n as usize,
+            RoundFormat::MeanOf3 => 3,
+            RoundFormat::AverageOf5 => 5,
+        }
+    }
+}
+
+/// Serialized as the format ID used by the WCIF ("1", "2", "3", "m", "a").
+impl Serialize for RoundFormat {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            RoundFormat::BestOf(n) => serializer.collect_str(n),
+            RoundFormat::MeanOf3 => serializer.serialize_str("m"),
+            RoundFormat::AverageOf5 => serializer.serialize_str("a"),
+        }
+    }
+}
+
+impl TryFrom<&str> for RoundFormat {
+    type Error = WCOError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "1" => Ok(RoundFormat::BestOf(1)),
+            "2" => Ok(RoundFormat::BestOf(2)),
+            "3" => Ok(RoundFormat::BestOf(3)),
+            "m" => Ok(RoundFormat::MeanOf3),
+            "a" => Ok(RoundFormat::AverageOf5),
+            _ => Err(WCOError::ParsingError(format!(
+                r#"Failed to parse "{}" into a round format"#,
+                value
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
 pub struct Competitor {
     pub name: String,
     pub wca_id: Option<String>,
     pub events: Vec<Event>,
-    pub personal_records: HashMap<Event, Duration>,
+    #[serde(serialize_with = "serialize_sorted")]
+    pub personal_records: HashMap<Event, PersonalRecord>,
+    pub retrieval_error: Option<String>,
+    /// ISO 3166-1 alpha-2 code of the country the competitor represents
+    pub country_iso2: Option<String>,
+    pub gender: Option<Gender>,
+    /// Most recent attempts per event, newest first. `None` is a DNF or DNS.
+    #[serde(serialize_with = "serialize_sorted")]
+    pub recent_attempts: HashMap<Event, Vec<Option<ResultValue>>>,
+    #[serde(serialize_with = "serialize_sorted")]
+    pub chances: HashMap<Event, Chances>,
+    /// ID of the competitor's registration in the WCIF
+    pub registrant_id: Option<u64>,
 }
 
 impl Competitor {
@@ -17,6 +238,69 @@ impl Competitor {
             wca_id,
             events,
             personal_records: HashMap::new(),
+            retrieval_error: None,
+            country_iso2: None,
+            gender: None,
+            recent_attempts: HashMap::new(),
+            chances: HashMap::new(),
+            registrant_id: None,
+        }
+    }
+
+    pub fn country(&self) -> Option<Country> {
+        self.country_iso2.as_deref().and_then(country_by_iso2)
+    }
+}
+
+/// Serializes a map ordered by its keys, so that the output does not depend on the hash order.
+fn serialize_sorted<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Serialize + Ord,
+    V: Serialize,
+    S: Serializer,
+{
+    serializer.collect_map(map.iter().collect::<BTreeMap<_, _>>())
+}
+
+/// Simulated probabilities (0 to 1) of a competitor's possible outcomes in an event.
+#[derive(Debug, Default, PartialEq, Copy, Clone, Serialize)]
+pub struct Chances {
+    pub win: f64,
+    pub podium: f64,
+    /// Probability to advance from the first round, `None` for events with a single round
+    pub advance: Option<f64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Gender {
+    Female,
+    Male,
+    Other,
+}
+
+impl Gender {
+    pub fn pretty_name(&self) -> &'static str {
+        match self {
+            Gender::Female => "Female",
+            Gender::Male => "Male",
+            Gender::Other => "Other",
+        }
+    }
+}
+
+impl TryFrom<&str> for Gender {
+    type Error = WCOError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "f" | "female" => Ok(Gender::Female),
+            "m" | "male" => Ok(Gender::Male),
+            "o" | "other" => Ok(Gender::Other),
+            _ => Err(WCOError::ParsingError(format!(
+                r#"Failed to parse "{}" into a gender"#,
+                value
+            ))),
         }
     }
 }
@@ -31,7 +315,7 @@ pub enum Event {
     Ev666,
     Ev777,
     Ev333bf,
-    //Ev333fm,
+    Ev333fm,
     Ev333oh,
     EvClock,
     EvMinx,
@@ -40,10 +324,30 @@ pub enum Event {
     EvSq1,
     Ev444bf,
     Ev555bf,
-    //Ev333mbf,
+    Ev333mbf,
 }
 
 impl Event {
+    pub const ALL: [Event; 17] = [
+        Event::Ev333,
+        Event::Ev222,
+        Event::Ev444,
+        Event::Ev555,
+        Event::Ev666,
+        Event::Ev777,
+        Event::Ev333bf,
+        Event::Ev333fm,
+        Event::Ev333oh,
+        Event::EvClock,
+        Event::EvMinx,
+        Event::EvPyram,
+        Event::EvSkewb,
+        Event::EvSq1,
+        Event::Ev444bf,
+        Event::Ev555bf,
+        Event::Ev333mbf,
+    ];
+
     pub fn pretty_name(&self) -> &'static str {
         match self {
             Event::Ev333 => "3x3x3 Cube",
@@ -53,7 +357,7 @@ impl Event {
             Event::Ev666 => "6x6x6 Cube",
             Event::Ev777 => "7x7x7 Cube",
             Event::Ev333bf => "3x3x3 Blindfolded",
-            //Event::Ev333fm => "3x3x3 Fewest Moves",
+            Event::Ev333fm => "3x3x3 Fewest Moves",
             Event::Ev333oh => "3x3x3 One-Handed",
             Event::EvClock => "Clock",
             Event::EvMinx => "Megaminx",
@@ -62,7 +366,7 @@ impl Event {
             Event::EvSq1 => "Square-1",
             Event::Ev444bf => "4x4x4 Blindfolded",
             Event::Ev555bf => "5x5x5 Blindfolded",
-            //Event::Ev333mbf => "3x3x3 Multi-Blind",
+            Event::Ev333mbf => "3x3x3 Multi-Blind",
         }
     }
 
@@ -75,7 +379,7 @@ impl Event {
             Event::Ev666 => "666",
             Event::Ev777 => "777",
             Event::Ev333bf => "333bf",
-            //Event::Ev333fm => "333fm",
+            Event::Ev333fm => "333fm",
             Event::Ev333oh => "333oh",
             Event::EvClock => "clock",
             Event::EvMinx => "minx",
@@ -84,16 +388,291 @@ impl Event {
             Event::EvSq1 => "sq1",
             Event::Ev444bf => "444bf",
             Event::Ev555bf => "555bf",
-            //Event::Ev333mbf => "333mbf",
+            Event::Ev333mbf => "333mbf",
         }
     }
 
-    pub fn use_average(&self) -> bool {
+    /// The record type the WCA uses for official rankings of this event.
+    pub fn default_record_type(&self) -> RecordType {
         match self {
-            Event::Ev333bf | Event::Ev444bf | Event::Ev555bf => false,
-            _ => true,
+            Event::Ev333bf | Event::Ev444bf | Event::Ev555bf | Event::Ev333mbf => {
+                RecordType::Single
+            }
+            _ => RecordType::Average,
         }
     }
+
+    pub fn has_average(&self) -> bool {
+        *self != Event::Ev333mbf
+    }
+
+    /// Round format most competitions use for this event, for when the WCIF is unavailable.
+    pub fn default_round_format(&self) -> RoundFormat {
+        match self {
+            Event::Ev333bf | Event::Ev444bf | Event::Ev555bf => RoundFormat::BestOf(3),
+            Event::Ev333mbf => RoundFormat::BestOf(1),
+            Event::Ev666 | Event::Ev777 | Event::Ev333fm => RoundFormat::MeanOf3,
+            _ => RoundFormat::AverageOf5,
+        }
+    }
+}
+
+/// Serialized as the WCA event ID, e.g. "333".
+impl Serialize for Event {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code_name())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordType {
+    Single,
+    Average,
+}
+
+impl RecordType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RecordType::Single => "Single",
+            RecordType::Average => "Average",
+        }
+    }
+}
+
+/// World, continental and national rank of a record.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
+pub struct Ranks {
+    pub world: u32,
+    pub continent: u32,
+    pub country: u32,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone, Serialize)]
+pub struct PersonalRecord {
+    pub single: Option<ResultValue>,
+    pub average: Option<ResultValue>,
+    pub single_ranks: Option<Ranks>,
+    pub average_ranks: Option<Ranks>,
+}
+
+impl PersonalRecord {
+    pub fn set(&mut self, record_type: RecordType, value: ResultValue, ranks: Option<Ranks>) {
+        match record_type {
+            RecordType::Single => {
+                self.single = Some(value);
+                self.single_ranks = ranks;
+            }
+            RecordType::Average => {
+                self.average = Some(value);
+                self.average_ranks = ranks;
+            }
+        }
+    }
+
+    pub fn ranks(&self, record_type: RecordType) -> Option<&Ranks> {
+        match record_type {
+            RecordType::Single => self.single_ranks.as_ref(),
+            RecordType::Average => self.average_ranks.as_ref(),
+        }
+    }
+
+    pub fn get(&self, record_type: RecordType) -> Option<&ResultValue> {
+        match record_type {
+            RecordType::Single => self.single.as_ref(),
+            RecordType::Average => self.average.as_ref(),
+        }
+    }
+
+    /// Sort key for rankings by `record_type`. Records without a value of that type are placed
+    /// behind all others and ranked by the other record type.
+    pub fn ranking_key(
+        &self,
+        record_type: RecordType,
+    ) -> (bool, Option<&ResultValue>, Option<&ResultValue>) {
+        let other = match record_type {
+            RecordType::Single => RecordType::Average,
+            RecordType::Average => RecordType::Single,
+        };
+        let preferred = self.get(record_type);
+        (preferred.is_none(), preferred, self.get(other))
+    }
+}
+
+/// A single result or average as it is ranked by the WCA. Depending on the event, this is either
+/// a time, a number of moves or a multi-blind score. Smaller values are better results.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum ResultValue {
+    Time(Duration),
+    /// Number of moves in hundredths, since averages are not necessarily whole numbers
+    Moves(u32),
+    MultiBlind {
+        solved: u32,
+        attempted: u32,
+        time: Duration,
+    },
+}
+
+impl ResultValue {
+    /// Decodes the integer representation used by the WCA (results export, APIs).
+    pub fn from_wca_value(event: Event, value: u32, is_average: bool) -> Self {
+        match (event, is_average) {
+            (Event::Ev333mbf, _) => decode_multiblind(value),
+            (Event::Ev333fm, true) => ResultValue::Moves(value),
+            (Event::Ev333fm, false) => ResultValue::Moves(value * 100),
+            _ => ResultValue::Time(Duration::new(
+                value as u64 / 100,
+                (value % 100) * 10 * 1000 * 1000,
+            )),
+        }
+    }
+
+    /// Decodes a single attempt as stored by the WCA. `None` for a DNF (-1) or DNS (-2).
+    pub fn from_wca_attempt(event: Event, value: i64) -> Option<Self> {
+        u32::try_from(value)
+            .ok()
+            .filter(|value| *value > 0)
+            .map(|value| ResultValue::from_wca_value(event, value, false))
+    }
+
+    /// Integer that orders results like `Ord`, suitable to compute means of times and move counts.
+    pub fn sort_value(&self) -> u64 {
+        match self {
+            ResultValue::Time(time) => time.as_millis() as u64 / 10,
+            ResultValue::Moves(hundredths) => *hundredths as u64,
+            ResultValue::MultiBlind {
+                solved,
+                attempted,
+                time,
+            } => {
+                let points = (99 - self.multiblind_points()).max(0) as u64;
+                points * 10_000_000 + time.as_secs() * 100 + (attempted - solved) as u64
+            }
+        }
+    }
+
+    /// Position of the result on the axis of a plot: seconds, moves or multi-blind points.
+    pub fn histogram_value(&self) -> f64 {
+        match self {
+            ResultValue::Time(time) => time.as_secs_f64(),
+            ResultValue::Moves(hundredths) => *hundredths as f64 / 100.0,
+            ResultValue::MultiBlind { .. } => self.multiblind_points().max(0) as f64,
+        }
+    }
+
+    /// Solved minus unsolved cubes, the primary ranking criterion of multi-blind.
+    pub fn multiblind_points(&self) -> i64 {
+        match self {
+            ResultValue::MultiBlind {
+                solved, attempted, ..
+            } => 2 * *solved as i64 - *attempted as i64,
+            _ => 0,
+        }
+    }
+
+    fn kind_index(&self) -> u8 {
+        match self {
+            ResultValue::Time(_) => 0,
+            ResultValue::Moves(_) => 1,
+            ResultValue::MultiBlind { .. } => 2,
+        }
+    }
+}
+
+/// Serialized as an object with a "type" ("time", "moves" or "multiblind") and the values of
+/// that type: times in centiseconds, moves as a number (averages have decimals), multi-blind as
+/// solved and attempted cubes and the time in seconds.
+impl Serialize for ResultValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ResultValue::Time(time) => {
+                let mut state = serializer.serialize_struct("ResultValue", 2)?;
+                state.serialize_field("type", "time")?;
+                state.serialize_field("centiseconds", &(time.as_millis() as u64 / 10))?;
+                state.end()
+            }
+            ResultValue::Moves(hundredths) => {
+                let mut state = serializer.serialize_struct("ResultValue", 2)?;
+                state.serialize_field("type", "moves")?;
+                state.serialize_field("moves", &(*hundredths as f64 / 100.0))?;
+                state.end()
+            }
+            ResultValue::MultiBlind {
+                solved,
+                attempted,
+                time,
+            } => {
+                let mut state = serializer.serialize_struct("ResultValue", 4)?;
+                state.serialize_field("type", "multiblind")?;
+                state.serialize_field("solved", solved)?;
+                state.serialize_field("attempted", attempted)?;
+                state.serialize_field("seconds", &time.as_secs())?;
+                state.end()
+            }
+        }
+    }
+}
+
+impl Ord for ResultValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (ResultValue::Time(a), ResultValue::Time(b)) => a.cmp(b),
+            (ResultValue::Moves(a), ResultValue::Moves(b)) => a.cmp(b),
+            (
+                ResultValue::MultiBlind {
+                    solved: solved_a,
+                    attempted: attempted_a,
+                    time: time_a,
+                },
+                ResultValue::MultiBlind {
+                    solved: solved_b,
+                    attempted: attempted_b,
+                    time: time_b,
+                },
+            ) => other
+                .multiblind_points()
+                .cmp(&self.multiblind_points())
+                .then(time_a.cmp(time_b))
+                .then((attempted_a - solved_a).cmp(&(attempted_b - solved_b))),
+            _ => self.kind_index().cmp(&other.kind_index()),
+        }
+    }
+}
+
+impl PartialOrd for ResultValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Time of old multi-blind results whose time was not recorded. Sorts them behind all results
+/// with the same points, like the WCA does.
+pub const UNKNOWN_MULTIBLIND_TIME: Duration = Duration::from_secs(99_999);
+
+/// Decodes multi-blind results, both in the current format `0DDTTTTTMM` (99 - points, seconds,
+/// missed cubes) and the old format `1SSAATTTTT` (99 - solved, attempted, seconds).
+fn decode_multiblind(value: u32) -> ResultValue {
+    let (solved, attempted, seconds) = match value / 1_000_000_000 {
+        1 => {
+            let solved = 99 - (value / 10_000_000) % 100;
+            let attempted = (value / 100_000) % 100;
+            (solved, attempted, value % 100_000)
+        }
+        _ => {
+            let points = 99 - (value / 10_000_000) % 100;
+            let missed = value % 100;
+            (
+                points + missed,
+                points + 2 * missed,
+                (value / 100) % 100_000,
+            )
+        }
+    };
+    ResultValue::MultiBlind {
+        solved,
+        attempted,
+        time: Duration::from_secs(seconds as u64),
+    }
 }
 
 impl TryFrom<&str> for Event {
@@ -108,7 +687,7 @@ impl TryFrom<&str> for Event {
             "666" => Ok(Event::Ev666),
             "777" => Ok(Event::Ev777),
             "333bf" => Ok(Event::Ev333bf),
-            //"333fm" => Ok(Event::Ev333fm),
+            "333fm" => Ok(Event::Ev333fm),
             "333oh" => Ok(Event::Ev333oh),
             "clock" => Ok(Event::EvClock),
             "minx" => Ok(Event::EvMinx),
@@ -117,7 +696,7 @@ impl TryFrom<&str> for Event {
             "sq1" => Ok(Event::EvSq1),
             "444bf" => Ok(Event::Ev444bf),
             "555bf" => Ok(Event::Ev555bf),
-            //"333mbf" => Ok(Event::Ev333mbf),
+            "333mbf" => Ok(Event::Ev333mbf),
             _ => Err(WCOError::ParsingError(format!(
                 r#"Failed to parse "{}" into an event"#,
                 value
@@ -125,3 +704,108 @@ impl TryFrom<&str> for Event {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_competition_ids_and_urls() {
+        let id = |input: &str| CompetitionId::from_str(input).unwrap().as_str().to_owned();
+        assert_eq!(id("HessenMiniOpen2024"), "HessenMiniOpen2024");
+        assert_eq!(id("  HessenMiniOpen2024\n"), "HessenMiniOpen2024");
+        let base = "https://www.worldcubeassociation.org/competitions/HessenMiniOpen2024";
+        for url in [
+            base.to_owned(),
+            format!("{}/", base),
+            format!("{}?tab=events", base),
+            format!("{}#competition-info", base),
+            format!("{}/registrations", base),
+            format!("{}/results/all?event=333", base),
+            format!("{}/events", base.replace("https://", "")),
+        ] {
+            assert_eq!(id(&url), "HessenMiniOpen2024", "{}", url);
+        }
+    }
+
+    #[test]
+    fn rejects_other_input() {
+        for input in [
+            "",
+            "Hessen Mini Open 2024",
+            "https://www.worldcubeassociation.org/persons/2009ZEMD01",
+            "https://www.worldcubeassociation.org/competitions",
+            "https://www.worldcubeassociation.org/competitions/",
+            "https://www.worldcubeassociation.org/mycompetitions/HessenMiniOpen2024",
+        ] {
+            assert!(CompetitionId::from_str(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn serializes_maps_in_event_order() {
+        let events = [Event::EvPyram, Event::Ev333, Event::Ev222, Event::Ev333bf];
+        let map: HashMap<_, _> = events.iter().map(|event| (*event, 0)).collect();
+        let mut json = Vec::new();
+        serialize_sorted(&map, &mut serde_json::Serializer::new(&mut json)).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            r#"{"333":0,"222":0,"333bf":0,"pyram":0}"#
+        );
+    }
+
+    #[test]
+    fn decodes_fewest_moves_values() {
+        // singles are whole moves, means are stored in hundredths
+        assert_eq!(
+            ResultValue::from_wca_value(Event::Ev333fm, 25, false),
+            ResultValue::Moves(2500)
+        );
+        assert_eq!(
+            ResultValue::from_wca_value(Event::Ev333fm, 2833, true),
+            ResultValue::Moves(2833)
+        );
+        assert_eq!(
+            ResultValue::from_wca_attempt(Event::Ev333fm, 31),
+            Some(ResultValue::Moves(3100))
+        );
+        // DNF and DNS
+        assert_eq!(ResultValue::from_wca_attempt(Event::Ev333fm, -1), None);
+        assert_eq!(ResultValue::from_wca_attempt(Event::Ev333fm, -2), None);
+    }
+
+    #[test]
+    fn decodes_current_multiblind_format() {
+        // 9/10 in 58:12: 99 - 8 points, 3492 seconds, 1 missed cube
+        assert_eq!(
+            ResultValue::from_wca_value(Event::Ev333mbf, 910349201, false),
+            ResultValue::MultiBlind {
+                solved: 9,
+                attempted: 10,
+                time: Duration::from_secs(3492),
+            }
+        );
+        // 8/8 with unknown time
+        assert_eq!(
+            ResultValue::from_wca_value(Event::Ev333mbf, 919999900, false),
+            ResultValue::MultiBlind {
+                solved: 8,
+                attempted: 8,
+                time: UNKNOWN_MULTIBLIND_TIME,
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_old_multiblind_format() {
+        // 9/10 in 58:12: 99 - 9 solved, 10 attempted, 3492 seconds
+        assert_eq!(
+            ResultValue::from_wca_value(Event::Ev333mbf, 1901003492, false),
+            ResultValue::MultiBlind {
+                solved: 9,
+                attempted: 10,
+                time: Duration::from_secs(3492),
+            }
+        );
+    }
+}
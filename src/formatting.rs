@@ -0,0 +1,73 @@
+//! Formatting of results and durations shared by all output formats.
+
+use std::time::Duration;
+
+use crate::datastructures::{ResultValue, UNKNOWN_MULTIBLIND_TIME};
+
+/// Duration rounded to minutes, e.g. "45 min" or "1 h 05 min".
+pub fn format_duration(duration: &Duration) -> String {
+    let minutes = (duration.as_secs() + 30) / 60;
+    match minutes / 60 {
+        0 => format!("{} min", minutes),
+        hours => format!("{} h {:0>2} min", hours, minutes % 60),
+    }
+}
+
+/// Probability as a percentage with one decimal, without the percent sign.
+pub fn format_percentage(probability: f64) -> String {
+    format!("{:.1}", probability * 100.0)
+}
+
+/// Result as shown in the tables, e.g. "1:02.34", "28.33" moves or "9/10 58:12".
+pub fn format_result(record: &ResultValue) -> String {
+    match record {
+        ResultValue::Time(time) => format_time(time),
+        ResultValue::Moves(hundredths) if hundredths % 100 == 0 => (hundredths / 100).to_string(),
+        ResultValue::Moves(hundredths) => format!("{}.{:0>2}", hundredths / 100, hundredths % 100),
+        ResultValue::MultiBlind {
+            solved,
+            attempted,
+            time,
+        } if *time == UNKNOWN_MULTIBLIND_TIME => format!("{}/{}", solved, attempted),
+        ResultValue::MultiBlind {
+            solved,
+            attempted,
+            time,
+        } => format!(
+            "{}/{} {}:{:0>2}",
+            solved,
+            attempted,
+            time.as_secs() / 60,
+            time.as_secs() % 60
+        ),
+    }
+}
+
+pub fn format_time(time: &Duration) -> String {
+    let subsec = time.subsec_millis() / 10;
+    let sec = time.as_secs() % 60;
+    let min = time.as_secs() / 60;
+    match min {
+        0 => format!("{}.{:0>2}", sec, subsec),
+        _ => format!("{}:{:0>2}.{:0>2}", min, sec, subsec),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_multiblind_results() {
+        let result = |time| ResultValue::MultiBlind {
+            solved: 9,
+            attempted: 10,
+            time,
+        };
+        assert_eq!(
+            format_result(&result(Duration::from_secs(3492))),
+            "9/10 58:12"
+        );
+        assert_eq!(format_result(&result(UNKNOWN_MULTIBLIND_TIME)), "9/10");
+    }
+}
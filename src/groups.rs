@@ -0,0 +1,431 @@
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::datastructures::{Competition, CompetitionId, Competitor, Event, RecordType};
+use crate::wcoerror::WCOError;
+
+/// How the fastest competitors of an event are placed into groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupStrategy {
+    /// Every group gets a similar mix of fast and slow competitors
+    Spread,
+    /// The fastest competitors compete together in the last group
+    Cluster,
+}
+
+impl FromStr for GroupStrategy {
+    type Err = WCOError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "spread" => Ok(GroupStrategy::Spread),
+            "cluster" => Ok(GroupStrategy::Cluster),
+            _ => Err(WCOError::ParsingError(format!(
+                "Invalid group strategy specified: \"{}\"",
+                s
+            ))),
+        }
+    }
+}
+
+/// Groups of the first round of an event.
+pub struct EventGroups<'a> {
+    pub event: Event,
+    pub ranking: RecordType,
+    pub groups: Vec<Vec<&'a Competitor>>,
+    /// WCIF activity IDs of the groups, empty if the schedule does not define the groups
+    pub activity_ids: Vec<u64>,
+}
+
+/// Settings shared by the group assignment of all events.
+pub struct GroupSettings<'a> {
+    /// Number of groups of every event, instead of the number from the schedule
+    pub group_count: Option<usize>,
+    /// Maximum group size, used to determine the number of groups if neither `group_count` nor
+    /// the schedule define it
+    pub max_group_size: usize,
+    pub strategy: GroupStrategy,
+    /// WCA IDs or names of staff members
+    pub staff: &'a HashSet<String>,
+}
+
+impl GroupSettings<'_> {
+    pub fn is_staff(&self, competitor: &Competitor) -> bool {
+        self.staff.contains(&competitor.name)
+            || competitor
+                .wca_id
+                .as_ref()
+                .is_some_and(|id| self.staff.contains(id))
+    }
+}
+
+/// Splits the participants of the first round of `event` into groups of balanced size.
+/// Staff members and newcomers are distributed evenly across the groups first, so that every
+/// group has staff to help in the other groups and newcomers do not end up on their own.
+/// The returners are placed according to the strategy.
+pub fn assign_groups<'a>(
+    competition: &Competition,
+    competitors: &'a [Competitor],
+    event: &Event,
+    ranking: RecordType,
+    settings: &GroupSettings,
+) -> EventGroups<'a> {
+    let mut participants: Vec<_> = competitors
+        .iter()
+        .filter(|comp| comp.events.contains(event))
+        .collect();
+    // fastest first, competitors without a PR last
+    participants.sort_by_key(|comp| {
+        comp.personal_records
+            .get(event)
+            .map(|pr| pr.ranking_key(ranking))
+            .unwrap_or((true, None, None))
+    });
+
+    let mut first_round_slots: Vec<_> = competition
+        .schedule
+        .iter()
+        .filter(|scheduled| scheduled.event == *event && scheduled.round == 1)
+        .collect();
+    first_round_slots.sort_by_key(|scheduled| (scheduled.start, scheduled.room.clone()));
+    // a round without groups in the schedule is a single group
+    let activity_ids: Vec<_> = first_round_slots
+        .iter()
+        .flat_map(|scheduled| match scheduled.group_activity_ids.is_empty() {
+            true => vec![scheduled.activity_id],
+            false => scheduled.group_activity_ids.clone(),
+        })
+        .collect();
+    let group_count = settings
+        .group_count
+        .or((!activity_ids.is_empty()).then_some(activity_ids.len()))
+        .unwrap_or_else(|| participants.len().div_ceil(settings.max_group_size.max(1)))
+        .clamp(1, participants.len().max(1));
+
+    // balanced sizes, earlier groups take the remainder
+    let mut capacities: Vec<_> = (0..group_count)
+        .map(|group| {
+            participants.len() / group_count + usize::from(group < participants.len() % group_count)
+        })
+        .collect();
+    let mut groups = vec![vec![]; group_count];
+    let mut next_group = 0;
+    let mut deal = |competitor, groups: &mut Vec<Vec<_>>, capacities: &mut Vec<usize>| {
+        while capacities[next_group] == 0 {
+            next_group = (next_group + 1) % group_count;
+        }
+        capacities[next_group] -= 1;
+        groups[next_group].push(competitor);
+        next_group = (next_group + 1) % group_count;
+    };
+
+    let (staff, others): (Vec<_>, Vec<_>) = participants
+        .into_iter()
+        .partition(|comp| settings.is_staff(comp));
+    let (newcomers, returners): (Vec<_>, Vec<_>) =
+        others.into_iter().partition(|comp| comp.wca_id.is_none());
+    for competitor in staff.into_iter().chain(newcomers) {
+        deal(competitor, &mut groups, &mut capacities);
+    }
+    match settings.strategy {
+        GroupStrategy::Spread => {
+            for competitor in returners {
+                deal(competitor, &mut groups, &mut capacities);
+            }
+        }
+        GroupStrategy::Cluster => {
+            // the fastest fill up the last group, the next fastest the group before and so on
+            let mut returners = returners.into_iter();
+            for group in (0..group_count).rev() {
+                let free = capacities[group];
+                groups[group].extend(returners.by_ref().take(free));
+            }
+        }
+    }
+
+    EventGroups {
+        event: *event,
+        ranking,
+        groups,
+        activity_ids,
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize)]
+struct WcifAssignmentsFile {
+    formatVersion: &'static str,
+    id: String,
+    persons: Vec<WcifPersonAssignments>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize)]
+struct WcifPersonAssignments {
+    registrantId: u64,
+    name: String,
+    wcaId: Option<String>,
+    assignments: Vec<WcifAssignment>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize)]
+struct WcifAssignment {
+    activityId: u64,
+    stationNumber: Option<u32>,
+    assignmentCode: &'static str,
+}
+
+pub struct WcifAssignments<'a, 'b> {
+    pub json: String,
+    /// Events left out because their groups do not match the groups of the schedule
+    pub skipped: Vec<&'b EventGroups<'a>>,
+}
+
+/// Partial WCIF with the competitor assignments of all groups, to be imported into tools that
+/// work with the WCIF. Events whose groups do not match the groups of the schedule are skipped.
+pub fn generate_wcif_assignments<'a, 'b>(
+    competition_id: &CompetitionId,
+    event_groups: &'b [EventGroups<'a>],
+) -> Result<WcifAssignments<'a, 'b>, WCOError> {
+    let mut persons: BTreeMap<u64, WcifPersonAssignments> = BTreeMap::new();
+    let mut skipped = vec![];
+    for event_groups in event_groups {
+        if event_groups.activity_ids.len() != event_groups.groups.len() {
+            skipped.push(event_groups);
+            continue;
+        }
+        for (group, activity_id) in event_groups.groups.iter().zip(&event_groups.activity_ids) {
+            for competitor in group {
+                let Some(registrant_id) = competitor.registrant_id else {
+                    continue;
+                };
+                persons
+                    .entry(registrant_id)
+                    .or_insert_with(|| WcifPersonAssignments {
+                        registrantId: registrant_id,
+                        name: competitor.name.clone(),
+                        wcaId: competitor.wca_id.clone(),
+                        assignments: vec![],
+                    })
+                    .assignments
+                    .push(WcifAssignment {
+                        activityId: *activity_id,
+                        stationNumber: None,
+                        assignmentCode: "competitor",
+                    });
+            }
+        }
+    }
+    let file = WcifAssignmentsFile {
+        formatVersion: "1.0",
+        id: competition_id.to_string(),
+        persons: persons.into_values().collect(),
+    };
+    Ok(WcifAssignments {
+        json: serde_json::to_string_pretty(&file)?,
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{DateTime, Utc};
+
+    use super::*;
+    use crate::datastructures::{PersonalRecord, ResultValue, ScheduledRound};
+
+    fn competition(schedule: Vec<ScheduledRound>) -> Competition {
+        Competition {
+            name: "Test Open 2024".to_owned(),
+            start_date: None,
+            end_date: None,
+            events: vec![],
+            schedule,
+        }
+    }
+
+    fn first_round(group_activity_ids: Vec<u64>) -> ScheduledRound {
+        let start = DateTime::parse_from_rfc3339("2024-05-04T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        ScheduledRound {
+            activity_id: 1,
+            event: Event::Ev333,
+            round: 1,
+            attempt: None,
+            room: "Main".to_owned(),
+            start,
+            end: start,
+            timezone: None,
+            group_activity_ids,
+        }
+    }
+
+    /// Returner "r{seconds}" with an average of `seconds`.
+    fn returner(seconds: u64) -> Competitor {
+        let mut competitor = Competitor::new(
+            format!("r{}", seconds),
+            Some(format!("2020RETU{:02}", seconds)),
+            vec![Event::Ev333],
+        );
+        let mut record = PersonalRecord::default();
+        let average = ResultValue::Time(Duration::from_secs(seconds));
+        record.set(RecordType::Average, average, None);
+        competitor.personal_records.insert(Event::Ev333, record);
+        competitor.registrant_id = Some(seconds);
+        competitor
+    }
+
+    fn newcomer(name: &str) -> Competitor {
+        Competitor::new(name.to_owned(), None, vec![Event::Ev333])
+    }
+
+    fn settings(
+        group_count: Option<usize>,
+        strategy: GroupStrategy,
+        staff: &HashSet<String>,
+    ) -> GroupSettings<'_> {
+        GroupSettings {
+            group_count,
+            max_group_size: 4,
+            strategy,
+            staff,
+        }
+    }
+
+    fn assign<'a>(
+        competition: &Competition,
+        competitors: &'a [Competitor],
+        settings: &GroupSettings,
+    ) -> EventGroups<'a> {
+        assign_groups(
+            competition,
+            competitors,
+            &Event::Ev333,
+            RecordType::Average,
+            settings,
+        )
+    }
+
+    fn names<'a>(event_groups: &EventGroups<'a>) -> Vec<Vec<&'a str>> {
+        event_groups
+            .groups
+            .iter()
+            .map(|group| group.iter().map(|comp| comp.name.as_str()).collect())
+            .collect()
+    }
+
+    fn sizes(event_groups: &EventGroups) -> Vec<usize> {
+        event_groups.groups.iter().map(Vec::len).collect()
+    }
+
+    #[test]
+    fn balances_group_sizes() {
+        let staff = HashSet::new();
+        let mut competitors: Vec<_> = (1..=8).map(returner).collect();
+        competitors.push(newcomer("n1"));
+        competitors.push(newcomer("n2"));
+        let settings = settings(None, GroupStrategy::Cluster, &staff);
+
+        // without a schedule, as many groups as the maximum group size requires
+        let groups = assign(&competition(vec![]), &competitors, &settings);
+        assert_eq!(sizes(&groups), [4, 3, 3]);
+        assert!(groups.activity_ids.is_empty());
+        // the groups of the schedule
+        let scheduled = competition(vec![first_round(vec![11, 12])]);
+        let groups = assign(&scheduled, &competitors, &settings);
+        assert_eq!(sizes(&groups), [5, 5]);
+        assert_eq!(groups.activity_ids, [11, 12]);
+        // a round without groups is a single group
+        let groups = assign(
+            &competition(vec![first_round(vec![])]),
+            &competitors,
+            &settings,
+        );
+        assert_eq!(sizes(&groups), [10]);
+        assert_eq!(groups.activity_ids, [1]);
+    }
+
+    #[test]
+    fn deals_staff_and_newcomers_round_robin() {
+        let staff: HashSet<_> = ["r1", "2020RETU02", "r3"]
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        let mut competitors: Vec<_> = (1..=6).map(returner).collect();
+        competitors.extend(["n1", "n2", "n3"].map(newcomer));
+        let settings = settings(Some(3), GroupStrategy::Cluster, &staff);
+        let groups = assign(&competition(vec![]), &competitors, &settings);
+        assert_eq!(
+            names(&groups),
+            [
+                vec!["r1", "n1", "r6"],
+                vec!["r2", "n2", "r5"],
+                vec!["r3", "n3", "r4"]
+            ]
+        );
+    }
+
+    #[test]
+    fn places_the_fastest_by_strategy() {
+        let staff = HashSet::new();
+        let competitors: Vec<_> = (1..=6).rev().map(returner).collect();
+        let spread = assign(
+            &competition(vec![]),
+            &competitors,
+            &settings(Some(2), GroupStrategy::Spread, &staff),
+        );
+        assert_eq!(names(&spread), [["r1", "r3", "r5"], ["r2", "r4", "r6"]]);
+        let cluster = assign(
+            &competition(vec![]),
+            &competitors,
+            &settings(Some(2), GroupStrategy::Cluster, &staff),
+        );
+        assert_eq!(names(&cluster), [["r4", "r5", "r6"], ["r1", "r2", "r3"]]);
+    }
+
+    #[test]
+    fn skips_events_whose_groups_do_not_match_the_schedule() {
+        let staff = HashSet::new();
+        let competitors: Vec<_> = (1..=4).map(returner).collect();
+        let scheduled = competition(vec![first_round(vec![11, 12])]);
+        let matching = assign(
+            &scheduled,
+            &competitors,
+            &settings(None, GroupStrategy::Spread, &staff),
+        );
+        let mut mismatched = assign(
+            &scheduled,
+            &competitors,
+            &settings(Some(3), GroupStrategy::Spread, &staff),
+        );
+        mismatched.event = Event::Ev222;
+        let event_groups = [matching, mismatched];
+
+        let id = CompetitionId::from_str("TestOpen2024").unwrap();
+        let assignments = generate_wcif_assignments(&id, &event_groups).unwrap();
+        assert_eq!(assignments.skipped.len(), 1);
+        assert_eq!(assignments.skipped[0].event, Event::Ev222);
+
+        let json: serde_json::Value = serde_json::from_str(&assignments.json).unwrap();
+        assert_eq!(json["id"], "TestOpen2024");
+        let persons = json["persons"].as_array().unwrap();
+        let activities: Vec<_> = persons
+            .iter()
+            .map(|person| {
+                let assignments = person["assignments"].as_array().unwrap();
+                assert_eq!(assignments.len(), 1);
+                (
+                    person["registrantId"].as_u64().unwrap(),
+                    assignments[0]["activityId"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(activities, [(1, 11), (2, 12), (3, 11), (4, 12)]);
+    }
+}
@@ -1,40 +1,158 @@
-use crate::datastructures::{Competitor, Event};
-use maud::html;
-use std::time::Duration;
+use crate::countries::flag;
+use crate::css_generation::css_content;
+use crate::datastructures::{
+    Competition, Competitor, Event, Ranks, RecordType, ResultValue, ScheduledRound,
+};
+use crate::formatting::{format_duration, format_percentage, format_result, format_time};
+use crate::groups::{EventGroups, GroupSettings, GroupStrategy};
+use crate::plot::{
+    plot_cumulative, plot_event_comparison, plot_histogram, plot_scatter, Plot, PlotFormat,
+    PlotSettings,
+};
+use crate::prediction::predict_event;
+use crate::report::{EventReport, Renderer, Report};
+use crate::schedule::{estimate_schedule, RoundEstimate, ATTEMPT_OVERHEAD, GROUP_CHANGEOVER};
+use crate::simulation::cutoff_chance;
+use crate::wcoerror::WCOError;
+use maud::{html, Markup, PreEscaped};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Reorders an event table by single or average, see `generate_event_html`
+const SORT_SCRIPT: &str = include_str!("../static_files/sort_standings.js");
+
+/// The browsable report: `index.html`, a page and a histogram per event and the schedule
+/// estimates.
+pub struct HtmlRenderer {
+    pub plot_settings: PlotSettings,
+}
+
+impl Renderer for HtmlRenderer {
+    fn render(&self, report: &Report, report_dir: &Path) -> Result<(), WCOError> {
+        let plot_dir = report_dir.join("plots");
+        if self.plot_settings.format == PlotFormat::Png && !plot_dir.exists() {
+            fs::create_dir(&plot_dir)?;
+        }
+        let settings = &self.plot_settings;
+        for event_report in &report.events {
+            let code_name = event_report.event.code_name();
+            let plots = EventPlots {
+                histogram: plot_histogram(
+                    event_report,
+                    settings,
+                    report_dir,
+                    &format!("hist{}", code_name),
+                )?,
+                cumulative: plot_cumulative(
+                    event_report,
+                    settings,
+                    report_dir,
+                    &format!("cdf{}", code_name),
+                )?,
+                scatter: plot_scatter(
+                    event_report,
+                    settings,
+                    report_dir,
+                    &format!("scatter{}", code_name),
+                )?,
+            };
+            fs::write(
+                report_dir.join(format!("{}.html", code_name)),
+                generate_event_html(report, event_report, &plots),
+            )?;
+        }
+        let comparison = plot_event_comparison(report, settings, report_dir, "events")?;
+        let competition = report.competition;
+        if !competition.schedule.is_empty() {
+            let estimates = estimate_schedule(report);
+            fs::write(
+                report_dir.join("schedule.html"),
+                generate_schedule_html(competition, &estimates),
+            )?;
+        }
+        fs::write(report_dir.join("styles.css"), css_content())?;
+        fs::write(
+            report_dir.join("index.html"),
+            generate_index_html(report, comparison.as_ref()),
+        )?;
+        Ok(())
+    }
+}
+
+/// Plots shown on an event page.
+pub struct EventPlots {
+    pub histogram: Plot,
+    pub cumulative: Plot,
+    /// Averages compared to 3x3x3, only for some events
+    pub scatter: Option<Plot>,
+}
 
 pub fn generate_event_html(
-    competition_title: &str,
-    competitor_data: &[Competitor],
-    event: &Event,
+    report: &Report,
+    event_report: &EventReport,
+    plots: &EventPlots,
 ) -> String {
-    let mut competitors_no_id = vec![];
-    let mut competitors_no_time = vec![];
-    let mut competitors_time = vec![];
-    let event_participating_competitors = competitor_data
+    let competition_title = &report.competition.name;
+    let event = &event_report.event;
+    let ranking = event_report.ranking;
+    let rounds = event_report.rounds;
+    let prediction = predict_event(event_report);
+    let podium = prediction.podium();
+    let num_time = event_report.ranked.len();
+    let all_competitors: Vec<_> = event_report.participants().collect();
+    let show_ranks = all_competitors.iter().any(|comp| {
+        comp.personal_records
+            .get(event)
+            .is_some_and(|record| record.ranks(ranking).is_some())
+    });
+    let record_columns = match (event.has_average(), show_ranks) {
+        (true, true) => 5,
+        (false, true) => 4,
+        (true, false) => 2,
+        (false, false) => 1,
+    };
+    let show_chances = all_competitors
         .iter()
-        .filter(|comp| comp.events.contains(event));
-    for comp in event_participating_competitors {
-        match comp {
-            Competitor { wca_id: None, .. } => competitors_no_id.push(comp),
-            Competitor {
-                wca_id: Some(_),
-                personal_records: hmap,
-                ..
-            } if !hmap.contains_key(event) => competitors_no_time.push(comp),
-            _ => competitors_time.push(comp),
+        .any(|comp| comp.chances.contains_key(event));
+    let show_advance = all_competitors
+        .iter()
+        .any(|comp| comp.chances.get(event).is_some_and(|c| c.advance.is_some()));
+    let first_round_cutoff = rounds.first().and_then(|round| round.cutoff);
+    let slower_than_cutoff = |competitor: &Competitor| {
+        let average = competitor
+            .personal_records
+            .get(event)
+            .and_then(|record| record.average.as_ref());
+        match (average, &first_round_cutoff) {
+            (Some(average), Some(cutoff)) => *average >= cutoff.result,
+            _ => false,
         }
-    }
-    let num_time = competitors_time.len();
-    let num_no_time = competitors_no_time.len();
-    let num_no_id = competitors_no_id.len();
-    competitors_time.sort_by_key(|comp| comp.personal_records.get(event));
-    let mut all_competitors = competitors_time;
-    all_competitors.append(&mut competitors_no_time);
-    all_competitors.append(&mut competitors_no_id);
+    };
+    let has_limits = rounds
+        .iter()
+        .any(|round| round.cutoff.is_some() || round.time_limit.is_some());
+    // position of every ranked participant when ordered by single or average, so that the table
+    // can be reordered in the browser
+    let sortable = event.has_average() && num_time > 0;
+    let sort_positions = |record_type| {
+        let mut order: Vec<usize> = (0..num_time).collect();
+        order.sort_by_key(|&index| {
+            event_report.ranked[index].personal_records[event].ranking_key(record_type)
+        });
+        let mut positions = vec![0; num_time];
+        for (position, index) in order.into_iter().enumerate() {
+            positions[index] = position + 1;
+        }
+        positions
+    };
+    let single_positions = sort_positions(RecordType::Single);
+    let average_positions = sort_positions(RecordType::Average);
     let evname = event.pretty_name();
     let markup = html! {
         html {
             head {
+                meta charset="utf-8";
                 title { (event.code_name()) "@" (competition_title) }
                 link rel="stylesheet" type="text/css" href="styles.css" {}
             }
@@ -44,20 +162,116 @@ pub fn generate_event_html(
                         (competition_title) ": " (evname)
                     }
                     p {
-                        "Of the " (competitor_data.len()) " competitors, there is a total of " b { (all_competitors.len()) } " participating in " (evname) " registered. They consists of:"
+                        "Of the " (report.competitors.len()) " competitors, there is a total of " b { (all_competitors.len()) } " participating in " (evname) " registered. They consists of:"
                     }
                     ul {
                         li {
                             b { (num_time) } ", who have competed in " (evname) " before"
                         }
                         li {
-                            b { (num_no_time) } ", who have competed at WCA events before, but not in " (evname)
+                            b { (event_report.without_record.len()) } ", who have competed at WCA events before, but not in " (evname)
                         }
                         li {
-                            b { (num_no_id) } ", who have never competed at a WCA event before"
+                            b { (event_report.newcomers.len()) } ", who have never competed at a WCA event before"
+                        }
+                        @if !event_report.failed.is_empty() {
+                            li {
+                                b { (event_report.failed.len()) } ", whose PRs could not be retrieved"
+                            }
+                        }
+                    }
+                    p {
+                        "Competitors are ranked by their PR " (ranking.name().to_lowercase()) "."
+                        @if sortable {
+                            " Click the single or average column header to reorder the table."
+                        }
+                        @if show_chances {
+                            " Win, podium and advancement chances are estimated by simulating the event many times from the competitors' recent results."
+                        }
+                    }
+                    (plot_markup(&plots.histogram))
+                    (plot_markup(&plots.cumulative))
+                    @if let Some(scatter) = &plots.scatter {
+                        h2 { "Compared to 3x3x3" }
+                        p {
+                            "Each point is a participant with PR averages in both " (evname) " and 3x3x3 Cube."
+                        }
+                        (plot_markup(scatter))
+                    }
+                    @if has_limits {
+                        h2 { "Cutoffs and time limits" }
+                        table {
+                            tr {
+                                th { "Round" }
+                                th { "Format" }
+                                th { "Time limit" }
+                                th { "Cutoff" }
+                                th { "Expected to make the cutoff" }
+                            }
+                            @for (index, round) in rounds.iter().enumerate() {
+                                tr {
+                                    td { (index + 1) }
+                                    td { (round.format.pretty_name()) }
+                                    td {
+                                        @if let Some(limit) = &round.time_limit {
+                                            (format_time(&limit.time))
+                                            @if limit.cumulative { " (cumulative)" }
+                                        }
+                                    }
+                                    td {
+                                        @if let Some(cutoff) = &round.cutoff {
+                                            (format_result(&cutoff.result)) " in " (cutoff.attempts)
+                                            @if cutoff.attempts == 1 { " attempt" } @else { " attempts" }
+                                        }
+                                    }
+                                    td {
+                                        @if let (Some(cutoff), Some(projection)) = (&round.cutoff, prediction.rounds.get(index)) {
+                                            (format!("{:.0}", projection.competitors.iter().map(|comp| cutoff_chance(comp, event, cutoff)).sum::<f64>()))
+                                            " of " (projection.competitors.len())
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        @if first_round_cutoff.is_some() {
+                            p {
+                                "The expected number of competitors making a cutoff is estimated from their recent results or, without those, from their PRs. "
+                                "Competitors whose PR average is slower than the cutoff of the first round are shown in italics."
+                            }
+                        }
+                    }
+                    @if !podium.is_empty() {
+                        h2 { "Projected podium" }
+                        p {
+                            "Assuming every competitor places according to their PR " (ranking.name().to_lowercase())
+                            ", this is how the event could play out. Competitors without a PR are assumed to place last."
+                        }
+                        ol {
+                            @for competitor in &podium {
+                                li { (country_flag(competitor)) (competitor.name) }
+                            }
+                        }
+                        @if prediction.rounds.len() > 1 {
+                            ul {
+                                @for (index, round) in prediction.rounds.iter().enumerate() {
+                                    li {
+                                        "Round " (index + 1)
+                                        @if let Some(r) = rounds.get(index) {
+                                            " (" (r.format.pretty_name()) ")"
+                                        }
+                                        ": " (round.competitors.len()) " competitors"
+                                        @if let Some(advancing) = round.advancing {
+                                            ", " (advancing) " expected to advance"
+                                        }
+                                    }
+                                }
+                            }
+                            h2 { "Likely finalists" }
+                            p {
+                                (prediction.finalists().iter().map(|comp| comp.name.as_str()).collect::<Vec<_>>().join(", "))
+                            }
                         }
                     }
-                    img src=(format!("plots/hist{}.png", event.code_name())) {}
                     table {
                         tr {
                             th {}
@@ -65,22 +279,164 @@ pub fn generate_event_html(
                                 "Competitor"
                             }
                             th {
-                                (evname) " PR " ( if event.use_average() { "Average" } else { "Single" })
+                                @if sortable {
+                                    button.sort.active[ranking == RecordType::Single] type="button" onclick="sortStandings(this, 'single')" {
+                                        (evname) " PR Single"
+                                    }
+                                } @else {
+                                    (evname) " PR Single"
+                                }
+                            }
+                            @if event.has_average() {
+                                th {
+                                    @if sortable {
+                                        button.sort.active[ranking == RecordType::Average] type="button" onclick="sortStandings(this, 'average')" {
+                                            (evname) " PR Average"
+                                        }
+                                    } @else {
+                                        (evname) " PR Average"
+                                    }
+                                }
+                            }
+                            @if show_ranks {
+                                th { (ranking.name()) " WR" }
+                                th { (ranking.name()) " CR" }
+                                th { (ranking.name()) " NR" }
+                            }
+                            @if show_chances {
+                                th { "Win %" }
+                                th { "Podium %" }
+                                @if show_advance {
+                                    th { "Advance %" }
+                                }
                             }
                         }
                         @for (rank, competitor) in all_competitors.iter().enumerate() {
-                            tr {
-                                td { (match &competitor.personal_records.get(event) { Some(_) => (rank+1).to_string(), None => "".to_string()}) }
+                            tr class=[slower_than_cutoff(competitor).then_some("slower-than-cutoff")]
+                                data-single=[(sortable && rank < num_time).then(|| single_positions[rank])]
+                                data-average=[(sortable && rank < num_time).then(|| average_positions[rank])] {
+                                td { (if rank < num_time { (rank + 1).to_string() } else { "".to_string() }) }
                                 @if let Some(id) = &competitor.wca_id {
                                     td {
+                                        (country_flag(competitor))
                                         a target="_blank" href=(format!("https://www.worldcubeassociation.org/persons/{}", id)) {
                                             (competitor.name)
                                         }
                                     }
                                 } @ else {
-                                    td { (competitor.name) }
+                                    td { (country_flag(competitor)) (competitor.name) }
+                                }
+                                @if let Some(record) = competitor.personal_records.get(event) {
+                                    td { (record.single.as_ref().map(format_result).unwrap_or_default()) }
+                                    @if event.has_average() {
+                                        td { (record.average.as_ref().map(format_result).unwrap_or_default()) }
+                                    }
+                                    @if show_ranks {
+                                        @if let Some(ranks) = record.ranks(ranking) {
+                                            td { (ranks.world) }
+                                            td { (ranks.continent) }
+                                            td { (ranks.country) }
+                                        } @else {
+                                            td {} td {} td {}
+                                        }
+                                    }
+                                } @else if competitor.retrieval_error.is_some() {
+                                    td colspan=(record_columns) {
+                                        i { "PR unavailable: fetch failed" }
+                                    }
+                                } @else {
+                                    @for _ in 0..record_columns {
+                                        td {}
+                                    }
+                                }
+                                @if show_chances {
+                                    @let chances = competitor.chances.get(event).copied().unwrap_or_default();
+                                    td { (format_percentage(chances.win)) }
+                                    td { (format_percentage(chances.podium)) }
+                                    @if show_advance {
+                                        td { (chances.advance.map(format_percentage).unwrap_or_default()) }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                @if sortable {
+                    script { (PreEscaped(SORT_SCRIPT)) }
+                }
+            }
+        }
+    };
+    markup.into_string()
+}
+
+pub fn generate_schedule_html(competition: &Competition, estimates: &[RoundEstimate]) -> String {
+    let competition_title = &competition.name;
+    let overrun_count = estimates
+        .iter()
+        .filter(|estimate| estimate.overruns())
+        .count();
+    let markup = html! {
+        html {
+            head {
+                meta charset="utf-8";
+                title { (competition_title) " - Schedule" }
+                link rel="stylesheet" type="text/css" href="styles.css" {}
+            }
+            body {
+                div class="container" {
+                    h1 { (competition_title) " - Schedule" }
+                    p {
+                        "The duration of every scheduled round is estimated from the expected number of competitors, "
+                        "the groups defined in the schedule and the competitors' expected attempts (from recent results or PRs). "
+                        "Every attempt is assumed to take " (ATTEMPT_OVERHEAD.as_secs()) " seconds for inspection and scrambling in addition to solving, "
+                        "every group " (GROUP_CHANGEOVER.as_secs() / 60) " minutes to be called up. "
+                        "Competitors expected to miss a cutoff only do the cutoff attempts. "
+                        "Times are given in the local time of the venue."
+                    }
+                    p {
+                        @if overrun_count == 0 {
+                            "No round is likely to overrun its scheduled slot."
+                        } @else {
+                            b { (overrun_count) } " rounds are likely to overrun their scheduled slot and are highlighted."
+                        }
+                    }
+                    table {
+                        tr {
+                            th { "Start" }
+                            th { "Room" }
+                            th { "Round" }
+                            th { "Competitors" }
+                            th { "Groups" }
+                            th { "Typical attempt" }
+                            th { "Cutoff" }
+                            th { "Scheduled" }
+                            th { "Estimated" }
+                        }
+                        @for estimate in estimates {
+                            @let scheduled = estimate.scheduled;
+                            tr class=[estimate.overruns().then_some("overrun")] {
+                                td { (format_start(scheduled)) }
+                                td { (scheduled.room) }
+                                td {
+                                    a href=(format!("{}.html", scheduled.event.code_name())) {
+                                        (scheduled.event.pretty_name())
+                                    }
+                                    ", Round " (scheduled.round)
+                                    @if let Some(attempt) = scheduled.attempt {
+                                        ", Attempt " (attempt)
+                                    }
+                                }
+                                td { (estimate.competitors) }
+                                td { (estimate.groups) }
+                                td { (estimate.typical_attempt.as_ref().map(format_time).unwrap_or_default()) }
+                                td {
+                                    @if let (Some(cutoff), Some(making_cutoff)) = (&estimate.cutoff, estimate.making_cutoff) {
+                                        (format_result(&cutoff.result)) " (" (format!("{:.0}", making_cutoff)) " expected to make it)"
+                                    }
                                 }
-                                td { (match &competitor.personal_records.get(event) { Some(time) => format_time(time), None => "".to_string()}) }
+                                td { (format_duration(&scheduled.duration())) }
+                                td { (format_duration(&estimate.estimated)) }
                             }
                         }
                     }
@@ -91,39 +447,123 @@ pub fn generate_event_html(
     markup.into_string()
 }
 
-fn format_time(time: &Duration) -> String {
-    let subsec = time.subsec_millis() / 10;
-    let sec = time.as_secs() % 60;
-    let min = time.as_secs() / 60;
-    match min {
-        0 => format!("{}.{:0>2}", sec, subsec),
-        _ => format!("{}:{:0>2}.{:0>2}", min, sec, subsec),
+/// Start of a round in the venue's time zone, or in UTC (marked as such) if it is unknown.
+fn format_start(scheduled: &ScheduledRound) -> String {
+    match scheduled.timezone {
+        Some(timezone) => scheduled
+            .start
+            .with_timezone(&timezone)
+            .format("%a %H:%M")
+            .to_string(),
+        None => scheduled.start.format("%a %H:%M UTC").to_string(),
     }
 }
 
-pub fn generate_index_html(
-    competition_title: &str,
-    events: &[Event],
-    competitors: &[Competitor],
+pub fn generate_groups_html(
+    competition: &Competition,
+    event_groups: &[EventGroups],
+    settings: &GroupSettings,
 ) -> String {
-    let mut sorted_events: Vec<_> = events.iter().collect();
-    sorted_events.sort();
+    let competition_title = &competition.name;
+    let markup = html! {
+        html {
+            head {
+                meta charset="utf-8";
+                title { (competition_title) " - Groups" }
+                link rel="stylesheet" type="text/css" href="styles.css" {}
+            }
+            body {
+                div class="container" {
+                    h1 { (competition_title) " - Groups" }
+                    p {
+                        "Competitors of every first round are split into groups of similar size. "
+                        @match settings.strategy {
+                            GroupStrategy::Spread => "The fastest competitors are spread across all groups. ",
+                            GroupStrategy::Cluster => "The fastest competitors compete together in the last group. ",
+                        }
+                        "Newcomers and staff members (marked with *) are distributed evenly."
+                    }
+                    @for event_groups in event_groups {
+                        div class="group-event" {
+                            h2 { (event_groups.event.pretty_name()) }
+                            @for (index, group) in event_groups.groups.iter().enumerate() {
+                                h3 { "Group " (index + 1) " (" (group.len()) " competitors)" }
+                                table {
+                                    tr {
+                                        th { "Competitor" }
+                                        th { "WCA ID" }
+                                        th { "PR " (event_groups.ranking.name()) }
+                                    }
+                                    @for competitor in group {
+                                        tr {
+                                            td {
+                                                (country_flag(competitor)) (competitor.name)
+                                                @if settings.is_staff(competitor) { " *" }
+                                            }
+                                            td { (competitor.wca_id.as_deref().unwrap_or("newcomer")) }
+                                            td {
+                                                (competitor.personal_records.get(&event_groups.event)
+                                                    .and_then(|pr| pr.get(event_groups.ranking))
+                                                    .map(format_result)
+                                                    .unwrap_or_default())
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+    markup.into_string()
+}
+
+pub fn generate_index_html(report: &Report, comparison: Option<&Plot>) -> String {
+    let competition = report.competition;
+    let competitors = report.competitors;
+    let competition_title = &competition.name;
     let competitor_count = competitors.len();
-    let event_count = sorted_events.len();
-    let returner_count = competitors
-        .iter()
-        .filter(|comp| comp.wca_id.is_some())
-        .count();
-    let newcomer_count = competitor_count - returner_count;
+    let event_count = report.events.len();
+    let returner_count = report.returner_count();
+    let newcomer_count = report.newcomer_count();
+    let top_ranked = highest_ranked_competitors(competitors, 10);
+    let countries = breakdown(competitors, |comp| {
+        comp.country().map(|country| {
+            (
+                format!("{} {}", flag(country.iso2), country.name),
+                country.continent.pretty_name(),
+            )
+        })
+    });
+    let continents = breakdown(competitors, |comp| {
+        comp.country()
+            .map(|country| (country.continent.pretty_name().to_owned(), ""))
+    });
+    let genders = breakdown(competitors, |comp| {
+        comp.gender
+            .map(|gender| (gender.pretty_name().to_owned(), ""))
+    });
     let markup = html! {
         html {
             head {
+                meta charset="utf-8";
                 title { (competition_title) " - Competitor Overview" }
                 link rel="stylesheet" type="text/css" href="styles.css" {}
             }
             body {
                 div class="container" {
                     h1 { (competition_title) " - Competitor Overview" }
+                    @if let (Some(start), Some(end)) = (competition.start_date, competition.end_date) {
+                        p {
+                            @if start == end {
+                                "The competition takes place on " b { (start.format("%B %-d, %Y")) } "."
+                            } @else {
+                                "The competition takes place from " b { (start.format("%B %-d, %Y")) } " to " b { (end.format("%B %-d, %Y")) } "."
+                            }
+                        }
+                    }
                     p {
                         "There is a total of " b { (competitor_count) } " competitors registered across " b { (event_count) } " events. "
                         "The competitors consists of " b { (newcomer_count) } " newcomers and " b { (returner_count) } " returners."
@@ -146,8 +586,14 @@ pub fn generate_index_html(
                             th {
                                 "Available personal records"
                             }
+                            @if !competition.events.is_empty() {
+                                th {
+                                    "Rounds"
+                                }
+                            }
                         }
-                        @for event in sorted_events {
+                        @for event_report in &report.events {
+                            @let event = &event_report.event;
                             tr {
                                 td {
                                     a href=(format!("{}.html", event.code_name())) {
@@ -155,10 +601,103 @@ pub fn generate_index_html(
                                     }
                                 }
                                 td {
-                                    (competitors.iter().filter(|comp| comp.events.contains(event)).count())
+                                    (event_report.participant_count())
                                 }
                                 td {
-                                    (competitors.iter().filter(|comp| comp.personal_records.contains_key(event)).count())
+                                    (event_report.ranked.len())
+                                }
+                                @if !competition.events.is_empty() {
+                                    td {
+                                        @if let Some(comp_event) = competition.event(event) {
+                                            (comp_event.rounds.iter().map(|round| round.format.pretty_name()).collect::<Vec<_>>().join(", "))
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    @if !competition.schedule.is_empty() {
+                        p {
+                            "An estimate of how long each round will take, compared to the schedule, can be found on the "
+                            a href="schedule.html" { "schedule page" } "."
+                        }
+                    }
+                    @if let Some(comparison) = comparison {
+                        h2 { "Event comparison" }
+                        p {
+                            "The PRs of the participants of each event divided by the median PR of the event. The boxes span the "
+                            "middle half of the participants, points mark PRs far from the rest."
+                        }
+                        (plot_markup(comparison))
+                    }
+                    @if !countries.is_empty() {
+                        h2 { "Countries" }
+                        p {
+                            "The competitors represent " b { (countries.len()) } " countries from " b { (continents.len()) } " continents."
+                            @if !genders.is_empty() {
+                                " By gender, there are "
+                                (genders.iter().map(|(gender, _, total, _)| format!("{} {}", total, gender.to_lowercase())).collect::<Vec<_>>().join(", "))
+                                " competitors."
+                            }
+                        }
+                        table {
+                            tr {
+                                th { "Country" }
+                                th { "Continent" }
+                                th { "Competitors" }
+                                th { "Newcomers" }
+                            }
+                            @for (country, continent, total, newcomers) in &countries {
+                                tr {
+                                    td { (country) }
+                                    td { (continent) }
+                                    td { (total) }
+                                    td { (newcomers) }
+                                }
+                            }
+                        }
+                        table {
+                            tr {
+                                th { "Continent" }
+                                th { "Competitors" }
+                                th { "Newcomers" }
+                            }
+                            @for (continent, _, total, newcomers) in &continents {
+                                tr {
+                                    td { (continent) }
+                                    td { (total) }
+                                    td { (newcomers) }
+                                }
+                            }
+                        }
+                    }
+                    @if !top_ranked.is_empty() {
+                        h2 { "Highest-ranked competitors" }
+                        p {
+                            "The attending competitors with the best world ranks, each listed with their best-ranked record "
+                            "among the events they are registered for."
+                        }
+                        table {
+                            tr {
+                                th { "Competitor" }
+                                th { "Event" }
+                                th { "Record" }
+                                th { "WR" }
+                                th { "CR" }
+                                th { "NR" }
+                            }
+                            @for (competitor, event, record_type, value, ranks) in top_ranked {
+                                tr {
+                                    td { (competitor.name) }
+                                    td {
+                                        a href=(format!("{}.html", event.code_name())) {
+                                            (event.pretty_name())
+                                        }
+                                    }
+                                    td { (format_result(value)) " " (record_type.name().to_lowercase()) }
+                                    td { (ranks.world) }
+                                    td { (ranks.continent) }
+                                    td { (ranks.country) }
                                 }
                             }
                         }
@@ -169,3 +708,72 @@ pub fn generate_index_html(
     };
     markup.into_string()
 }
+
+/// Each competitor's record with the best world rank among their registered events, sorted by
+/// world rank.
+fn highest_ranked_competitors(
+    competitors: &[Competitor],
+    count: usize,
+) -> Vec<(&Competitor, Event, RecordType, &ResultValue, &Ranks)> {
+    let mut best: Vec<_> = competitors
+        .iter()
+        .filter_map(|comp| {
+            comp.events
+                .iter()
+                .filter_map(|event| comp.personal_records.get(event).map(|pr| (event, pr)))
+                .flat_map(|(event, pr)| {
+                    [RecordType::Single, RecordType::Average]
+                        .into_iter()
+                        .filter_map(move |rt| Some((comp, *event, rt, pr.get(rt)?, pr.ranks(rt)?)))
+                })
+                .min_by_key(|(_, _, _, _, ranks)| ranks.world)
+        })
+        .collect();
+    best.sort_by_key(|(_, _, _, _, ranks)| ranks.world);
+    best.truncate(count);
+    best
+}
+
+fn plot_markup(plot: &Plot) -> Markup {
+    match plot {
+        Plot::File {
+            path,
+            width,
+            height,
+        } => html! { img class="plot" src=(path) width=(width) height=(height) {} },
+        Plot::Svg(svg) => html! { div class="plot" { (PreEscaped(svg)) } },
+    }
+}
+
+fn country_flag(competitor: &Competitor) -> Markup {
+    html! {
+        @if let Some(country) = competitor.country() {
+            span title=(country.name) { (flag(country.iso2)) }
+            " "
+        }
+    }
+}
+
+/// Groups the competitors by the label returned from `key` (competitors without a label are
+/// skipped) and counts all competitors and newcomers per group, largest groups first.
+fn breakdown<F>(competitors: &[Competitor], key: F) -> Vec<(String, &'static str, usize, usize)>
+where
+    F: Fn(&Competitor) -> Option<(String, &'static str)>,
+{
+    let mut groups: HashMap<(String, &'static str), (usize, usize)> = HashMap::new();
+    for competitor in competitors {
+        if let Some(label) = key(competitor) {
+            let (total, newcomers) = groups.entry(label).or_default();
+            *total += 1;
+            if competitor.wca_id.is_none() {
+                *newcomers += 1;
+            }
+        }
+    }
+    let mut groups: Vec<_> = groups
+        .into_iter()
+        .map(|((label, detail), (total, newcomers))| (label, detail, total, newcomers))
+        .collect();
+    groups.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    groups
+}
@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::data_retrieval::SourceCapabilities;
+use crate::datastructures::{Competition, Competitor, Event, RecordType, ResultValue};
+use crate::report::{EventReport, Renderer, Report, Statistics};
+use crate::wcoerror::WCOError;
+
+/// Version of the structure of `report.json`. Increased on every change that can break
+/// consumers, i.e. removed or renamed fields and changed meanings. New fields do not change it.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    schema_version: u32,
+    generated_at: DateTime<Utc>,
+    source: JsonSource<'a>,
+    competition: &'a Competition,
+    events: Vec<JsonEvent<'a>>,
+    competitors: &'a [Competitor],
+}
+
+#[derive(Serialize)]
+struct JsonSource<'a> {
+    name: &'a str,
+    capabilities: SourceCapabilities,
+}
+
+#[derive(Serialize)]
+struct JsonEvent<'a> {
+    event: Event,
+    ranking: RecordType,
+    participants: usize,
+    /// Participants with a PR of the event
+    with_record: usize,
+    /// Participants with a WCA ID, but without a PR of the event
+    without_record: usize,
+    newcomers: usize,
+    /// Participants whose PRs could not be retrieved
+    failed: usize,
+    /// Participants with a PR, ordered by `ranking`
+    standings: Vec<JsonStanding<'a>>,
+    statistics: Option<&'a Statistics>,
+}
+
+#[derive(Serialize)]
+struct JsonStanding<'a> {
+    rank: usize,
+    name: &'a str,
+    wca_id: Option<&'a str>,
+    single: Option<ResultValue>,
+    average: Option<ResultValue>,
+}
+
+/// `report.json` for bots and dashboards.
+pub struct JsonRenderer<'a> {
+    pub source_name: &'a str,
+    pub capabilities: SourceCapabilities,
+}
+
+impl Renderer for JsonRenderer<'_> {
+    fn render(&self, report: &Report, report_dir: &Path) -> Result<(), WCOError> {
+        fs::write(
+            report_dir.join("report.json"),
+            generate_report_json(report, self.source_name, self.capabilities)?,
+        )?;
+        Ok(())
+    }
+}
+
+pub fn generate_report_json(
+    report: &Report,
+    source_name: &str,
+    capabilities: SourceCapabilities,
+) -> Result<String, WCOError> {
+    let json_report = JsonReport {
+        schema_version: JSON_SCHEMA_VERSION,
+        generated_at: Utc::now(),
+        source: JsonSource {
+            name: source_name,
+            capabilities,
+        },
+        competition: report.competition,
+        events: report.events.iter().map(json_event).collect(),
+        competitors: report.competitors,
+    };
+    Ok(serde_json::to_string_pretty(&json_report)?)
+}
+
+fn json_event<'a>(event_report: &'a EventReport) -> JsonEvent<'a> {
+    let event = &event_report.event;
+    JsonEvent {
+        event: *event,
+        ranking: event_report.ranking,
+        participants: event_report.participant_count(),
+        with_record: event_report.ranked.len(),
+        without_record: event_report.without_record.len(),
+        newcomers: event_report.newcomers.len(),
+        failed: event_report.failed.len(),
+        standings: event_report
+            .ranked
+            .iter()
+            .enumerate()
+            .map(|(index, comp)| JsonStanding {
+                rank: index + 1,
+                name: &comp.name,
+                wca_id: comp.wca_id.as_deref(),
+                single: comp.personal_records[event].single,
+                average: comp.personal_records[event].average,
+            })
+            .collect(),
+        statistics: event_report.statistics.as_ref(),
+    }
+}
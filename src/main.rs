@@ -1,47 +1,103 @@
 use chrono::prelude::*;
 use indicatif::ProgressBar;
-use reqwest::blocking::get;
-use scraper::Html;
 use std::collections::HashSet;
+use std::fs;
 use std::path::PathBuf;
-use std::{fs, str::FromStr};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 use css_generation::css_content;
-use data_retrieval::competitorslist::get_competition_title;
-use data_retrieval::competitorslist::parse_competitors;
-use data_retrieval::pr_data_random::set_random_competitor_pr;
-use data_retrieval::pr_data_unofficialapi::retrieve_competitor_pr;
-use data_retrieval::pr_data_wcawebsite::retrieve_competitor_pr_avg_html;
-use html_generation::generate_event_html;
-use html_generation::generate_index_html;
-use plot::plot;
+use csv_generation::{generate_groups_csv, CsvRenderer};
+use data_retrieval::cache::{CacheMode, HttpCache};
+use data_retrieval::competitorslist;
+use data_retrieval::fetcher::{Fetcher, RetryPolicy};
+use data_retrieval::retrieve_all_records;
+use data_retrieval::source_registry::SourceRegistry;
+use data_retrieval::wcif;
+use data_retrieval::{SourceCapabilities, SourceConfig};
+use datastructures::{Competition, CompetitionId, Competitor, Event, RecordType};
+use groups::{assign_groups, generate_wcif_assignments, GroupSettings, GroupStrategy};
+use html_generation::generate_groups_html;
+use html_generation::HtmlRenderer;
+use json_generation::JsonRenderer;
+use markdown_generation::{MarkdownRenderer, TextRenderer};
+use plot::{PlotFormat, PlotSettings};
+use report::{Renderer, Report};
+use simulation::simulate_event;
 use wcoerror::WCOError;
 
+mod countries;
 mod css_generation;
+mod csv_generation;
 mod data_retrieval;
 mod datastructures;
+mod formatting;
+mod groups;
 mod html_generation;
+mod json_generation;
+mod markdown_generation;
 mod plot;
+mod prediction;
+mod report;
+mod schedule;
+mod simulation;
 mod wcoerror;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Source {
-    UnofficialAPI,
-    WCAwebsite,
-    Debug,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ranking {
+    Auto,
+    Single,
+    Average,
 }
-impl FromStr for Source {
+impl FromStr for Ranking {
     type Err = WCOError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_ascii_lowercase().as_str() {
-            "wcawebsite" => Ok(Source::WCAwebsite),
-            "unofficialapi" => Ok(Source::UnofficialAPI),
-            "debug" => Ok(Source::Debug),
+            "auto" => Ok(Ranking::Auto),
+            "single" => Ok(Ranking::Single),
+            "average" => Ok(Ranking::Average),
             _ => Err(WCOError::ParsingError(format!(
-                "Invalid source specified: \"{}\"",
+                "Invalid ranking specified: \"{}\"",
+                s
+            ))),
+        }
+    }
+}
+impl Ranking {
+    fn record_type(&self, event: &Event) -> RecordType {
+        match self {
+            Ranking::Single => RecordType::Single,
+            Ranking::Average if event.has_average() => RecordType::Average,
+            _ => event.default_record_type(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Html,
+    Json,
+    Csv,
+    Markdown,
+    Text,
+}
+
+impl FromStr for OutputFormat {
+    type Err = WCOError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "html" => Ok(OutputFormat::Html),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "text" => Ok(OutputFormat::Text),
+            _ => Err(WCOError::ParsingError(format!(
+                "Invalid output format specified: \"{}\"",
                 s
             ))),
         }
@@ -49,51 +105,392 @@ impl FromStr for Source {
 }
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
-    /// URL of the competition's competitor list page
-    #[arg(index = 1)]
-    url: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Competition ID (e.g. HessenMiniOpen2024) or URL of any of the competition's pages
+    #[arg(index = 1, required = true)]
+    competition: Option<CompetitionId>,
 
     /// Directory where to save the report (default: current directory)
-    #[arg(short, long, default_value_t = String::from("."))]
+    #[arg(short, long, default_value_t = String::from("."), global = true)]
     destination_directory: String,
 
-    /// Source where to retrieve PR averages from. Available: UnofficialAPI, WCAwebsite
-    #[arg(short, long, default_value = "UnofficialAPI")]
-    source: Source,
+    /// Source where to retrieve PR averages from. Available: UnofficialAPI, WCAwebsite, WCAexport
+    #[arg(short, long, default_value = "UnofficialAPI", global = true)]
+    source: String,
+
+    /// Path to a downloaded WCA results export (zip file or extracted directory), used by the WCAexport source
+    #[arg(long, global = true)]
+    export_path: Option<PathBuf>,
+
+    /// PR type that drives rankings and histograms. Available: Auto (as in the WCA rankings), Single, Average
+    #[arg(short, long, default_value = "Auto", global = true)]
+    ranking: Ranking,
 
     /// Do not open generated report in system default browser
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, default_value_t = false, global = true)]
     no_browser: bool,
+
+    /// Number of competitors whose PRs are retrieved in parallel
+    #[arg(short, long, default_value_t = 4, global = true)]
+    jobs: usize,
+
+    /// Maximum number of requests per second sent to a single host (0 disables the limit)
+    #[arg(long, default_value_t = 5.0, global = true)]
+    max_requests_per_second: f64,
+
+    /// Base URL of the WCA website, e.g. to use a mirror
+    #[arg(
+        long,
+        default_value = "https://www.worldcubeassociation.org",
+        global = true
+    )]
+    wca_url: String,
+
+    /// Base URL of the unofficial WCA API, e.g. to use a mirror
+    #[arg(
+        long,
+        default_value = "https://raw.githubusercontent.com/robiningelbrecht/wca-rest-api/master/api",
+        global = true
+    )]
+    api_url: String,
+
+    /// Seconds after which a single request is aborted
+    #[arg(long, default_value_t = 30, global = true)]
+    timeout: u64,
+
+    /// How often a request is retried after a temporary failure
+    #[arg(long, default_value_t = 3, global = true)]
+    retries: u32,
+
+    /// Keep competitors whose PRs could not be retrieved in the report instead of aborting
+    #[arg(short, long, default_value_t = false, global = true)]
+    keep_going: bool,
+
+    /// Directory for cached downloads (default: ~/.cache/wca_comp_overview)
+    #[arg(long, global = true)]
+    cache_dir: Option<PathBuf>,
+
+    /// Number of hours after which cached downloads are fetched again
+    #[arg(long, default_value_t = 24.0, global = true)]
+    cache_ttl: f64,
+
+    /// Only use cached data, fail if something is not cached
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with = "refresh",
+        global = true
+    )]
+    offline: bool,
+
+    /// Ignore cached data and download everything again
+    #[arg(long, default_value_t = false, global = true)]
+    refresh: bool,
+
+    /// Report formats to generate, separated by commas. Available: HTML, JSON (report.json), CSV (<event>.csv and competitors.csv), Markdown (report.md), Text (printed to the terminal)
+    #[arg(short, long, value_delimiter = ',', default_value = "HTML")]
+    format: Vec<OutputFormat>,
+
+    /// Number of competitors per event in the text output
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+
+    /// Image format of the plots in the HTML report. Available: PNG, SVG (embedded into the pages)
+    #[arg(long, default_value = "PNG")]
+    plot_format: PlotFormat,
+
+    /// Width of the plots in pixels
+    #[arg(long, default_value_t = 1000)]
+    plot_width: u32,
+
+    /// Height of the plots in pixels
+    #[arg(long, default_value_t = 400)]
+    plot_height: u32,
+
+    /// Factor by which PNG plots are rendered larger than displayed, for high-DPI screens
+    #[arg(long, default_value_t = 2)]
+    plot_scale: u32,
+
+    /// Width of the histogram bars in seconds (moves for FMC, points for multi-blind) (default: chosen from the spread of the PRs)
+    #[arg(long, value_parser = parse_bin_width)]
+    bin_width: Option<f64>,
+
+    /// Leave PRs far slower or faster than the rest out of the histograms
+    #[arg(long, default_value_t = false)]
+    clip_outliers: bool,
+
+    /// Estimate win, podium and advancement chances by simulating the competition from recent results
+    #[arg(long, default_value_t = false)]
+    simulate: bool,
+
+    /// Number of simulated competitions
+    #[arg(long, default_value_t = 1000)]
+    iterations: usize,
+
+    /// Seed of the simulation, the same seed leads to the same chances (default: random)
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Assign the competitors of every first round to groups instead of generating a report
+    Groups(GroupArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct GroupArgs {
+    /// Competition ID (e.g. HessenMiniOpen2024) or URL of any of the competition's pages
+    #[arg(index = 1)]
+    competition: CompetitionId,
+
+    /// Number of groups per event (default: as defined in the schedule, otherwise as many as --max-group-size requires)
+    #[arg(long)]
+    groups: Option<usize>,
+
+    /// Maximum number of competitors per group if the number of groups is not known
+    #[arg(long, default_value_t = 20)]
+    max_group_size: usize,
+
+    /// Placement of the fastest competitors. Available: Spread (across all groups), Cluster (together in the last group)
+    #[arg(long, default_value = "Spread")]
+    strategy: GroupStrategy,
+
+    /// Text file with the WCA IDs or names of staff members, one per line, to distribute them evenly
+    #[arg(long)]
+    staff: Option<PathBuf>,
+
+    /// Also save the assignments as WCIF JSON
+    #[arg(long, default_value_t = false)]
+    wcif: bool,
+}
+
+/// Value parser of `--bin-width`, which has to be a positive number of seconds, moves or points.
+fn parse_bin_width(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(width) if width.is_finite() && width > 0.0 => Ok(width),
+        _ => Err(format!("\"{}\" is not a positive number", value)),
+    }
 }
 
 fn main() -> Result<(), WCOError> {
     let args = Args::parse();
 
-    let report_index = generate_report(&args)?;
-    if !args.no_browser {
-        webbrowser::open(
-            report_index
-                .to_str()
-                .ok_or(WCOError::IOError(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "path to index of generated report is not valid",
-                )))?,
-        )?;
+    let report_index = match &args.command {
+        Some(Command::Groups(group_args)) => Some(generate_groups(&args, group_args)?),
+        None => generate_report(&args)?,
+    };
+    if let (Some(report_index), false) = (report_index, args.no_browser) {
+        webbrowser::open(report_index.to_str().ok_or(WCOError::IOError(
+            std::io::Error::other("path to index of generated report is not valid"),
+        ))?)?;
     }
     Ok(())
 }
 
-fn generate_report(args: &Args) -> Result<PathBuf, WCOError> {
-    let competitors_html = Html::parse_document(&get(&args.url)?.text()?);
-    let competition_title = get_competition_title(&competitors_html)?;
+/// Generates the report in all requested formats. Returns the index page if HTML was generated.
+fn generate_report(args: &Args) -> Result<Option<PathBuf>, WCOError> {
+    let competition_id = args
+        .competition
+        .as_ref()
+        .ok_or_else(|| WCOError::ParsingError("No competition specified".to_owned()))?;
+    let (competition, mut competitors, capabilities) =
+        load_competition_data(args, competition_id, args.simulate)?;
+    let all_events: HashSet<_> = competitors
+        .iter()
+        .flat_map(|comp| comp.events.iter().copied())
+        .collect();
+    if args.simulate {
+        let seed = args.seed.unwrap_or_else(rand::random);
+        println!(
+            "Simulating the competition {} times (seed {})...",
+            args.iterations, seed
+        );
+        for event in &all_events {
+            let rounds = competition
+                .event(event)
+                .map(|comp_event| comp_event.rounds.as_slice())
+                .unwrap_or_default();
+            simulate_event(&mut competitors, event, rounds, args.iterations, seed);
+        }
+    }
+    let events: Vec<_> = all_events
+        .into_iter()
+        .map(|event| (event, args.ranking.record_type(&event)))
+        .collect();
+    let report = Report::new(&competition, &competitors, &events);
+    let json_renderer = JsonRenderer {
+        source_name: &args.source,
+        capabilities,
+    };
+    let html_renderer = HtmlRenderer {
+        plot_settings: PlotSettings {
+            format: args.plot_format,
+            width: args.plot_width,
+            height: args.plot_height,
+            scale: args.plot_scale,
+            bin_width: args.bin_width,
+            clip_outliers: args.clip_outliers,
+        },
+    };
+    let text_renderer = TextRenderer { top: args.top };
+    let renderers: Vec<&dyn Renderer> = args
+        .format
+        .iter()
+        .map(|format| -> &dyn Renderer {
+            match format {
+                OutputFormat::Html => &html_renderer,
+                OutputFormat::Json => &json_renderer,
+                OutputFormat::Csv => &CsvRenderer,
+                OutputFormat::Markdown => &MarkdownRenderer,
+                OutputFormat::Text => &text_renderer,
+            }
+        })
+        .collect();
+    let report_dir = match renderers.iter().any(|renderer| renderer.writes_files()) {
+        true => create_report_dir(
+            args,
+            &create_foldername(&competition.name, capabilities.synthetic),
+        )?,
+        // nothing is written, e.g. only a text summary is printed
+        false => PathBuf::from(&args.destination_directory),
+    };
+    for renderer in renderers {
+        renderer.render(&report, &report_dir)?;
+    }
+    print_retrieval_failures(&competitors);
+    Ok(args
+        .format
+        .contains(&OutputFormat::Html)
+        .then(|| report_dir.join("index.html")))
+}
+
+fn generate_groups(args: &Args, group_args: &GroupArgs) -> Result<PathBuf, WCOError> {
+    let (competition, competitors, capabilities) =
+        load_competition_data(args, &group_args.competition, false)?;
+    let staff = match &group_args.staff {
+        Some(path) => fs::read_to_string(path)?
+            .lines()
+            .map(|line| line.trim().to_owned())
+            .filter(|line| !line.is_empty())
+            .collect(),
+        None => HashSet::new(),
+    };
+    let settings = GroupSettings {
+        group_count: group_args.groups,
+        max_group_size: group_args.max_group_size,
+        strategy: group_args.strategy,
+        staff: &staff,
+    };
+    let mut events: Vec<_> = competitors
+        .iter()
+        .flat_map(|comp| comp.events.iter().copied())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    events.sort();
+    let event_groups: Vec<_> = events
+        .iter()
+        .map(|event| {
+            let ranking = args.ranking.record_type(event);
+            assign_groups(&competition, &competitors, event, ranking, &settings)
+        })
+        .collect();
+
+    let groups_dir = create_report_dir(
+        args,
+        &format!(
+            "{}_groups",
+            create_foldername(&competition.name, capabilities.synthetic)
+        ),
+    )?;
+    fs::write(groups_dir.join("styles.css"), css_content())?;
+    fs::write(
+        groups_dir.join("groups.csv"),
+        generate_groups_csv(&event_groups, &settings),
+    )?;
+    if group_args.wcif {
+        let assignments = generate_wcif_assignments(&group_args.competition, &event_groups)?;
+        for event_groups in &assignments.skipped {
+            println!(
+                "The schedule defines {} groups for {}, but {} were assigned, skipping it in the WCIF assignments",
+                event_groups.activity_ids.len(),
+                event_groups.event.pretty_name(),
+                event_groups.groups.len()
+            );
+        }
+        fs::write(groups_dir.join("assignments.json"), assignments.json)?;
+    }
+    let groups_file = groups_dir.join("groups.html");
+    fs::write(
+        &groups_file,
+        generate_groups_html(&competition, &event_groups, &settings),
+    )?;
+    print_retrieval_failures(&competitors);
+    Ok(groups_file)
+}
+
+/// Loads the competition and its competitors with their PRs. Recent results are only loaded if
+/// `load_history` is set.
+fn load_competition_data(
+    args: &Args,
+    competition_id: &CompetitionId,
+    load_history: bool,
+) -> Result<(Competition, Vec<Competitor>, SourceCapabilities), WCOError> {
+    let fetcher = Arc::new(Fetcher::new(
+        args.max_requests_per_second,
+        Duration::from_secs(args.timeout),
+        RetryPolicy {
+            retries: args.retries,
+            initial_backoff: Duration::from_millis(500),
+        },
+        create_cache(args)?,
+    )?);
+    let (competition, mut competitors) = load_competition(args, competition_id, &fetcher)?;
+    let competition_title = &competition.name;
     println!(r#"Found competition "{}""#, competition_title);
+    let source_config = SourceConfig {
+        fetcher: Arc::clone(&fetcher),
+        wca_url: args.wca_url.clone(),
+        api_url: args.api_url.clone(),
+        export_path: args.export_path.clone(),
+        history_for: load_history.then(|| {
+            competitors
+                .iter()
+                .filter_map(|comp| comp.wca_id.clone())
+                .collect()
+        }),
+    };
+    let source = SourceRegistry::default().create(&args.source, &source_config)?;
+    let capabilities = source.capabilities();
+    let num_competitors = competitors.len() as u64;
+    println!(
+        r#"Found {} competitors for competition "{}""#,
+        num_competitors, competition_title
+    );
+    println!(
+        "Retrieving competitor PRs from {} (singles: {}, averages: {}, ranks: {})...",
+        args.source, capabilities.singles, capabilities.averages, capabilities.ranks
+    );
+    let bar = ProgressBar::new(num_competitors);
+    retrieve_all_records(
+        source.as_ref(),
+        &mut competitors,
+        args.jobs,
+        args.keep_going,
+        &bar,
+    )?;
+    bar.finish();
+    Ok((competition, competitors, capabilities))
+}
+
+fn create_report_dir(args: &Args, folder_name: &str) -> Result<PathBuf, WCOError> {
     let parent_dir = PathBuf::from(&args.destination_directory).canonicalize()?;
-    let report_dir = parent_dir.join(create_foldername(
-        &competition_title,
-        args.source == Source::Debug,
-    ));
+    let report_dir = parent_dir.join(folder_name);
     if report_dir.exists() {
         println!(
             "Target folder {:?} already exists, overwriting contents",
@@ -103,54 +500,67 @@ fn generate_report(args: &Args) -> Result<PathBuf, WCOError> {
         println!("Saving report in {:?}", report_dir);
         fs::create_dir(&report_dir)?;
     }
-    let mut competitors = parse_competitors(&competitors_html);
-    let num_competitors = competitors.len() as u64;
-    println!(
-        r#"Found {} competitors for competition "{}""#,
-        num_competitors, competition_title
-    );
-    println!("Retrieving competitor PRs...");
-    let mut all_events = HashSet::new();
-    let bar = ProgressBar::new(num_competitors);
-    for competitor in &mut competitors {
-        for event in competitor.events.clone().into_iter() {
-            match args.source {
-                Source::UnofficialAPI => retrieve_competitor_pr(competitor, event)?,
-                Source::WCAwebsite => retrieve_competitor_pr_avg_html(competitor, event)?,
-                Source::Debug => set_random_competitor_pr(competitor, event),
-            }
-            all_events.insert(event);
+    Ok(report_dir)
+}
+
+/// Loads the competition from its WCIF, falling back to scraping the registrations page if the
+/// WCIF is not available.
+fn load_competition(
+    args: &Args,
+    competition_id: &CompetitionId,
+    fetcher: &Fetcher,
+) -> Result<(Competition, Vec<Competitor>), WCOError> {
+    match wcif::retrieve_competition(fetcher, &args.wca_url, competition_id.as_str()) {
+        Ok(loaded) => Ok(loaded),
+        Err(e) => {
+            println!(
+                "Could not load the competition's WCIF ({}), using the registrations page instead",
+                e
+            );
+            let registrations_url = competition_id.registrations_url(&args.wca_url);
+            competitorslist::retrieve_competition(fetcher, &registrations_url)
         }
-        bar.inc(1);
     }
-    bar.finish();
-    let plot_dir = report_dir.join("plots");
-    if !plot_dir.exists() {
-        fs::create_dir(&plot_dir)?;
-    }
-    for event in &all_events {
-        let event_html = generate_event_html(&competition_title, &competitors, &event);
-        let event_file = report_dir.join(format!("{}.html", event.code_name()));
-        fs::write(&event_file, event_html)?;
-
-        match plot(
-            &competitors,
-            &event,
-            &plot_dir.join(format!("hist{}.png", event.code_name())),
-        ) {
-            Err(e) => return Err(WCOError::PlottingError(e.to_string())),
-            _ => {}
-        }
+}
+
+fn print_retrieval_failures(competitors: &[Competitor]) {
+    let failures: Vec<_> = competitors
+        .iter()
+        .filter_map(|comp| comp.retrieval_error.as_ref().map(|e| (comp, e)))
+        .collect();
+    if failures.is_empty() {
+        return;
     }
-    fs::write(report_dir.join("styles.css"), css_content())?;
-    let index_html = generate_index_html(
-        &competition_title,
-        &all_events.into_iter().collect::<Vec<_>>(),
-        &competitors,
+    println!(
+        "PRs of {} competitors could not be retrieved, they are shown without PRs:",
+        failures.len()
     );
-    let index_file = report_dir.join("index.html");
-    fs::write(&index_file, index_html)?;
-    Ok(index_file)
+    for (competitor, error) in failures {
+        println!(
+            "  {} ({}): {}",
+            competitor.name,
+            competitor.wca_id.as_deref().unwrap_or_default(),
+            error
+        );
+    }
+}
+
+fn create_cache(args: &Args) -> Result<Option<HttpCache>, WCOError> {
+    let mode = match (args.offline, args.refresh) {
+        (true, _) => CacheMode::Offline,
+        (false, true) => CacheMode::Refresh,
+        (false, false) => CacheMode::Normal,
+    };
+    let ttl = Duration::try_from_secs_f64(args.cache_ttl * 3600.0).map_err(|_| {
+        WCOError::ParsingError(format!("Invalid cache TTL: {} hours", args.cache_ttl))
+    })?;
+    match args.cache_dir.clone().or_else(HttpCache::default_dir) {
+        Some(dir) => Ok(Some(HttpCache::new(dir, ttl, mode)?)),
+        None if mode == CacheMode::Offline => Err(WCOError::ParsingError(
+            "Offline mode requires a cache directory, pass --cache-dir".to_owned(),
+        )),
+        None => Ok(None),
+    }
 }
 
 fn create_foldername(comp_name: &str, debug: bool) -> String {
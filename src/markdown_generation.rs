@@ -0,0 +1,301 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use crate::datastructures::{Competitor, RecordType};
+use crate::formatting::format_result;
+use crate::report::{EventReport, Renderer, Report};
+use crate::wcoerror::WCOError;
+
+/// `report.md`
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn render(&self, report: &Report, report_dir: &Path) -> Result<(), WCOError> {
+        fs::write(
+            report_dir.join("report.md"),
+            generate_markdown_report(report),
+        )?;
+        Ok(())
+    }
+}
+
+/// Prints the summary to the terminal instead of writing files.
+pub struct TextRenderer {
+    /// Number of competitors per event
+    pub top: usize,
+}
+
+impl Renderer for TextRenderer {
+    fn render(&self, report: &Report, _report_dir: &Path) -> Result<(), WCOError> {
+        print!("{}", generate_text_summary(report, self.top));
+        Ok(())
+    }
+
+    fn writes_files(&self) -> bool {
+        false
+    }
+}
+
+/// Report as a single Markdown document, e.g. to paste a psych sheet into a forum post.
+pub fn generate_markdown_report(report: &Report) -> String {
+    let competition = report.competition;
+    let competitors = report.competitors;
+    let mut markdown = format!("# {} - Competitor Overview\n\n", escape(&competition.name));
+    if let (Some(start), Some(end)) = (competition.start_date, competition.end_date) {
+        if start == end {
+            let _ = writeln!(
+                markdown,
+                "The competition takes place on **{}**.\n",
+                start.format("%B %-d, %Y")
+            );
+        } else {
+            let _ = writeln!(
+                markdown,
+                "The competition takes place from **{}** to **{}**.\n",
+                start.format("%B %-d, %Y"),
+                end.format("%B %-d, %Y")
+            );
+        }
+    }
+    let _ = writeln!(
+        markdown,
+        "There is a total of **{}** competitors registered across **{}** events. \
+        The competitors consists of **{}** newcomers and **{}** returners.\n",
+        competitors.len(),
+        report.events.len(),
+        report.newcomer_count(),
+        report.returner_count()
+    );
+
+    markdown += "| Event | Participants | Personal records | Rounds |\n";
+    markdown += "|---|---:|---:|---|\n";
+    for event_report in &report.events {
+        let _ = writeln!(
+            markdown,
+            "| {} | {} | {} | {} |",
+            event_report.event.pretty_name(),
+            event_report.participant_count(),
+            event_report.ranked.len(),
+            event_report
+                .rounds
+                .iter()
+                .map(|round| round.format.pretty_name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    markdown += "\n";
+
+    for event_report in &report.events {
+        let event = &event_report.event;
+        let ranked = &event_report.ranked;
+        let unranked = unranked_participants(event_report);
+        let _ = writeln!(
+            markdown,
+            "## {}\n\nRanked by {}.\n",
+            event.pretty_name(),
+            event_report.ranking.name().to_lowercase()
+        );
+        if !ranked.is_empty() {
+            markdown += match event.has_average() {
+                true => "| # | Name | WCA ID | Single | Average |\n|---:|---|---|---:|---:|\n",
+                false => "| # | Name | WCA ID | Single |\n|---:|---|---|---:|\n",
+            };
+            for (index, competitor) in ranked.iter().enumerate() {
+                let record = &competitor.personal_records[event];
+                let format_value = |record_type| {
+                    record
+                        .get(record_type)
+                        .map(format_result)
+                        .unwrap_or_default()
+                };
+                let _ = write!(
+                    markdown,
+                    "| {} | {} | {} | {} |",
+                    index + 1,
+                    escape(&competitor.name),
+                    competitor.wca_id.as_deref().unwrap_or_default(),
+                    format_value(RecordType::Single)
+                );
+                if event.has_average() {
+                    let _ = write!(markdown, " {} |", format_value(RecordType::Average));
+                }
+                markdown += "\n";
+            }
+            markdown += "\n";
+        }
+        if !unranked.is_empty() {
+            let _ = writeln!(
+                markdown,
+                "Without a personal record: {}\n",
+                unranked
+                    .iter()
+                    .map(|comp| escape(&comp.name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+    markdown
+}
+
+/// Compact overview with the `top` competitors of every event for the terminal.
+pub fn generate_text_summary(report: &Report, top: usize) -> String {
+    let mut text = format!(
+        "{}: {} competitors, {} events\n",
+        report.competition.name,
+        report.competitors.len(),
+        report.events.len()
+    );
+    for event_report in &report.events {
+        let event = &event_report.event;
+        let ranking = event_report.ranking;
+        let ranked = &event_report.ranked;
+        let participant_count = event_report.participant_count();
+        let _ = writeln!(
+            text,
+            "\n{} ({}, {} participant{})",
+            event.pretty_name(),
+            ranking.name().to_lowercase(),
+            participant_count,
+            if participant_count == 1 { "" } else { "s" }
+        );
+        let name_width = ranked
+            .iter()
+            .take(top)
+            .map(|comp| comp.name.chars().count())
+            .max()
+            .unwrap_or_default();
+        for (index, competitor) in ranked.iter().take(top).enumerate() {
+            let record = &competitor.personal_records[event];
+            // competitors without the ranking type are ranked by their single
+            let (value, label) = match record.get(ranking) {
+                Some(value) => (format_result(value), ""),
+                None => (
+                    record
+                        .single
+                        .as_ref()
+                        .map(format_result)
+                        .unwrap_or_default(),
+                    " (single)",
+                ),
+            };
+            let _ = writeln!(
+                text,
+                "{:>4}. {:<name_width$}  {:>10}{}",
+                index + 1,
+                competitor.name,
+                value,
+                label
+            );
+        }
+        if ranked.len() > top {
+            let _ = writeln!(text, "      ... {} more", ranked.len() - top);
+        }
+        if participant_count > ranked.len() {
+            let _ = writeln!(
+                text,
+                "      {} without a personal record",
+                participant_count - ranked.len()
+            );
+        }
+    }
+    text
+}
+
+fn unranked_participants<'a>(event_report: &EventReport<'a>) -> Vec<&'a Competitor> {
+    event_report
+        .participants()
+        .skip(event_report.ranked.len())
+        .collect()
+}
+
+/// Escapes characters that would otherwise end a table cell or format the text.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '|' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '~' | '#' | '&'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::datastructures::{Competition, Event, PersonalRecord, ResultValue};
+
+    fn time(seconds: u64) -> ResultValue {
+        ResultValue::Time(Duration::from_secs(seconds))
+    }
+
+    fn competition() -> Competition {
+        Competition {
+            name: "Test Open 2024".to_owned(),
+            start_date: None,
+            end_date: None,
+            events: vec![],
+            schedule: vec![],
+        }
+    }
+
+    fn competitor(name: &str, single: u64, average: Option<u64>) -> Competitor {
+        let mut competitor = Competitor::new(
+            name.to_owned(),
+            Some(format!("2015TEST{:02}", single)),
+            vec![Event::Ev333],
+        );
+        let mut record = PersonalRecord::default();
+        record.set(RecordType::Single, time(single), None);
+        if let Some(average) = average {
+            record.set(RecordType::Average, time(average), None);
+        }
+        competitor.personal_records.insert(Event::Ev333, record);
+        competitor
+    }
+
+    #[test]
+    fn escapes_markdown_control_characters() {
+        assert_eq!(escape("Jane Doe"), "Jane Doe");
+        assert_eq!(escape("a|b"), "a\\|b");
+        assert_eq!(
+            escape("*_`[x](y)`_* <b> ~#&\\"),
+            "\\*\\_\\`\\[x\\](y)\\`\\_\\* \\<b\\> \\~\\#\\&\\\\"
+        );
+    }
+
+    #[test]
+    fn markdown_table_escapes_names() {
+        let competition = competition();
+        let competitors = [competitor("a_b*", 8, Some(10))];
+        let report = Report::new(
+            &competition,
+            &competitors,
+            &[(Event::Ev333, RecordType::Average)],
+        );
+        let markdown = generate_markdown_report(&report);
+        assert!(markdown.contains("| 1 | a\\_b\\* | 2015TEST08 | 8.00 | 10.00 |"));
+    }
+
+    #[test]
+    fn text_summary_labels_singles_used_for_ranking_by_average() {
+        let competition = competition();
+        let competitors = [competitor("Ann", 8, Some(10)), competitor("Bea", 7, None)];
+        let report = Report::new(
+            &competition,
+            &competitors,
+            &[(Event::Ev333, RecordType::Average)],
+        );
+        let text = generate_text_summary(&report, 5);
+        assert!(text.contains("   1. Ann       10.00\n"));
+        assert!(text.contains("   2. Bea        7.00 (single)\n"));
+    }
+}
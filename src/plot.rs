@@ -1,84 +1,848 @@
-use std::path::PathBuf;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
 
-use crate::datastructures::{Competitor, Event};
+use crate::datastructures::{Event, ResultValue};
+use crate::formatting::format_result;
+use crate::report::{EventReport, Report};
+use crate::wcoerror::WCOError;
+use plotters::coord::Shift;
 use plotters::{prelude::*, style::full_palette::GREY};
 
-struct PlotData {
+/// Bin widths the Freedman–Diaconis width is rounded to, so that bins start at round values.
+const NICE_BIN_WIDTHS: [f64; 16] = [
+    0.1, 0.2, 0.5, 1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 900.0, 1800.0,
+    3600.0,
+];
+
+/// Histograms are not split into more bins than this.
+const MAX_BIN_COUNT: usize = 1000;
+
+struct HistogramData {
+    /// Number of results per bin
     histogram_data: Vec<u64>,
-    lowest: u64,
-    highest: u64,
+    /// Lower edge of the first bin
+    lowest: f64,
+    bin_width: f64,
     max_count: u64,
+    /// Position of the cutoff line
+    cutoff: Option<f64>,
+    /// The values are seconds and labeled like times in the tables
+    time_axis: bool,
     title: String,
     x_desc: String,
     y_desc: String,
 }
 
-fn generate_plot_data(competitors: &[Competitor], event: &Event) -> PlotData {
-    let times_sec: Vec<_> = competitors
+fn generate_histogram_data(event_report: &EventReport, settings: &PlotSettings) -> HistogramData {
+    let event = &event_report.event;
+    let ranking = event_report.ranking;
+    let time_axis = !matches!(event, Event::Ev333fm | Event::Ev333mbf);
+    let mut values: Vec<_> = event_report
+        .ranked_values()
         .iter()
-        .filter_map(|comp| comp.personal_records.get(event).map(|time| time.as_secs()))
+        .map(ResultValue::histogram_value)
         .collect();
-    let fastest_time = *times_sec.iter().min().unwrap_or(&0);
-    let slowest_time = *times_sec.iter().max().unwrap_or(&0);
-    let bar_count = slowest_time - fastest_time + 1;
-    let mut histogram_data = vec![0u64; bar_count as usize];
-    for time in times_sec {
-        histogram_data[(time - fastest_time) as usize] += 1;
+    values.sort_by(f64::total_cmp);
+    let total = values.len();
+    if settings.clip_outliers {
+        if let Some((lower, upper)) = tukey_fences(&values) {
+            values.retain(|value| (lower..=upper).contains(value));
+        }
+    }
+    let clipped = total - values.len();
+    let cutoff = event_report
+        .rounds
+        .first()
+        .and_then(|round| round.cutoff)
+        .map(|cutoff| cutoff.result.histogram_value());
+
+    // the axis always covers the cutoff, even if all competitors are faster or slower
+    let lowest_value = values.iter().chain(&cutoff).copied().reduce(f64::min);
+    let highest_value = values.iter().chain(&cutoff).copied().reduce(f64::max);
+    let (lowest_value, highest_value) = (lowest_value.unwrap_or(0.0), highest_value.unwrap_or(0.0));
+    let bin_count = |width: f64| {
+        let lowest = (lowest_value / width).floor() * width;
+        ((highest_value - lowest) / width).floor() as usize + 1
+    };
+    let requested_width = settings
+        .bin_width
+        .unwrap_or_else(|| freedman_diaconis_width(&values));
+    // moves and points are whole numbers (apart from means of moves)
+    let requested_width = match time_axis {
+        true => requested_width,
+        false => requested_width.max(1.0),
+    };
+    // a few extreme PRs or a small --bin-width must not split the histogram into thousands of bins
+    let bin_width = std::iter::once(requested_width)
+        .chain(
+            NICE_BIN_WIDTHS
+                .into_iter()
+                .filter(|nice| *nice > requested_width),
+        )
+        .find(|width| bin_count(*width) <= MAX_BIN_COUNT)
+        .unwrap_or(requested_width);
+    if settings.bin_width.is_some() && bin_width != requested_width {
+        println!(
+            "A bin width of {} splits the {} histogram into {} bins, using {} instead",
+            requested_width,
+            event.pretty_name(),
+            bin_count(requested_width),
+            bin_width
+        );
+    }
+    let bin_count = bin_count(bin_width);
+    let lowest = (lowest_value / bin_width).floor() * bin_width;
+    let bin_index = |value: f64| ((value - lowest) / bin_width).floor() as usize;
+    let mut histogram_data = vec![0u64; bin_count];
+    for value in values {
+        histogram_data[bin_index(value).min(bin_count - 1)] += 1;
     }
     let max_count = *histogram_data.iter().max().unwrap_or(&0);
 
-    let title = format!(
-        "{} PR {} Histogram",
-        event.pretty_name(),
-        match event.use_average() {
-            true => "Average",
-            false => "Single",
-        }
-    );
+    let mut title = format!("{} PR {} Histogram", event.pretty_name(), ranking.name());
+    if clipped > 0 {
+        title += &format!(" ({} outliers not shown)", clipped);
+    }
 
-    PlotData {
+    HistogramData {
         histogram_data,
-        lowest: fastest_time,
-        highest: slowest_time,
+        lowest,
+        bin_width,
         max_count,
+        cutoff,
+        time_axis,
         title,
-        x_desc: "Solve time [s]".to_string(),
+        x_desc: match event {
+            Event::Ev333fm => "Moves".to_string(),
+            Event::Ev333mbf => "Points".to_string(),
+            _ => "Solve time".to_string(),
+        },
         y_desc: "Count".to_string(),
     }
 }
 
-fn generate_plot(pd: &PlotData, out_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let root_area = BitMapBackend::new(out_path, (1000, 400)).into_drawing_area();
+/// Linearly interpolated quantile of sorted values.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let below = sorted[position.floor() as usize];
+    let above = sorted[position.ceil() as usize];
+    below + (above - below) * position.fract()
+}
+
+/// Bin width of the Freedman–Diaconis rule, rounded to the closest nice value. Falls back to one
+/// unit if the values are too few or too similar.
+fn freedman_diaconis_width(sorted: &[f64]) -> f64 {
+    if sorted.len() < 2 {
+        return 1.0;
+    }
+    let iqr = quantile(sorted, 0.75) - quantile(sorted, 0.25);
+    if iqr <= 0.0 {
+        return 1.0;
+    }
+    let width = 2.0 * iqr / (sorted.len() as f64).cbrt();
+    NICE_BIN_WIDTHS
+        .into_iter()
+        .min_by(|a, b| (a / width).ln().abs().total_cmp(&(b / width).ln().abs()))
+        .unwrap_or(width)
+}
+
+/// Values outside of these bounds (1.5 interquartile ranges beyond the quartiles) are outliers.
+fn tukey_fences(sorted: &[f64]) -> Option<(f64, f64)> {
+    if sorted.len() < 4 {
+        return None;
+    }
+    let (q1, q3) = (quantile(sorted, 0.25), quantile(sorted, 0.75));
+    let iqr = q3 - q1;
+    Some((q1 - 1.5 * iqr, q3 + 1.5 * iqr))
+}
+
+/// Label of a position on the x axis, times formatted like in the tables (`m:ss`).
+fn axis_label(value: f64, time_axis: bool) -> String {
+    if !time_axis {
+        return match value.fract() {
+            fract if fract.abs() < 1e-6 => format!("{:.0}", value),
+            _ => format!("{:.1}", value),
+        };
+    }
+    let centiseconds = (value.max(0.0) * 100.0).round() as u64;
+    let label = format_result(&ResultValue::Time(Duration::from_millis(centiseconds * 10)));
+    match label.strip_suffix(".00") {
+        Some(whole) => whole.to_owned(),
+        None => label,
+    }
+}
+
+/// Share of the participants whose PR is at most as slow as the value.
+struct CumulativeData {
+    /// Sorted values of the ranking type
+    values: Vec<f64>,
+    cutoff: Option<f64>,
+    time_axis: bool,
+    title: String,
+    x_desc: String,
+}
+
+fn generate_cumulative_data(event_report: &EventReport) -> CumulativeData {
+    let event = &event_report.event;
+    let mut values: Vec<_> = event_report
+        .ranked_values()
+        .iter()
+        .map(ResultValue::histogram_value)
+        .collect();
+    values.sort_by(f64::total_cmp);
+    CumulativeData {
+        values,
+        cutoff: event_report
+            .rounds
+            .first()
+            .and_then(|round| round.cutoff)
+            .map(|cutoff| cutoff.result.histogram_value()),
+        time_axis: !matches!(event, Event::Ev333fm | Event::Ev333mbf),
+        title: format!(
+            "{} PR {} Distribution",
+            event.pretty_name(),
+            event_report.ranking.name()
+        ),
+        x_desc: match event {
+            Event::Ev333fm => "Moves".to_string(),
+            Event::Ev333mbf => "Points".to_string(),
+            _ => "Solve time".to_string(),
+        },
+    }
+}
+
+/// Box plot of one event with the PRs divided by the median PR.
+struct EventBox {
+    event: Event,
+    lower_whisker: f64,
+    lower_quartile: f64,
+    median: f64,
+    upper_quartile: f64,
+    upper_whisker: f64,
+    outliers: Vec<f64>,
+}
+
+fn generate_event_boxes(report: &Report) -> Vec<EventBox> {
+    report
+        .events
+        .iter()
+        .filter_map(|event_report| {
+            let mut values: Vec<_> = event_report
+                .ranked_values()
+                .iter()
+                .map(ResultValue::histogram_value)
+                .collect();
+            values.sort_by(f64::total_cmp);
+            if values.is_empty() {
+                return None;
+            }
+            // the same median as drawn in the box, so that it is always at 1
+            let median = quantile(&values, 0.5);
+            if median <= 0.0 {
+                return None;
+            }
+            let relative: Vec<_> = values.iter().map(|value| value / median).collect();
+            let (lower_fence, upper_fence) =
+                tukey_fences(&relative).unwrap_or((f64::NEG_INFINITY, f64::INFINITY));
+            let inside = || {
+                relative
+                    .iter()
+                    .copied()
+                    .filter(|value| (lower_fence..=upper_fence).contains(value))
+            };
+            Some(EventBox {
+                event: event_report.event,
+                lower_whisker: inside().reduce(f64::min)?,
+                lower_quartile: quantile(&relative, 0.25),
+                median: quantile(&relative, 0.5),
+                upper_quartile: quantile(&relative, 0.75),
+                upper_whisker: inside().reduce(f64::max)?,
+                outliers: relative
+                    .iter()
+                    .copied()
+                    .filter(|value| !(lower_fence..=upper_fence).contains(value))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// PR averages of the participants of an event against their 3x3x3 PR averages.
+struct ScatterData {
+    points: Vec<(f64, f64)>,
+    y_time_axis: bool,
+    title: String,
+    y_desc: String,
+}
+
+fn generate_scatter_data(event_report: &EventReport) -> ScatterData {
+    let event = &event_report.event;
+    let points = event_report
+        .ranked
+        .iter()
+        .filter_map(|comp| {
+            let average = |event| comp.personal_records.get(event)?.average.as_ref();
+            Some((
+                average(&Event::Ev333)?.histogram_value(),
+                average(event)?.histogram_value(),
+            ))
+        })
+        .collect();
+    ScatterData {
+        points,
+        y_time_axis: *event != Event::Ev333fm,
+        title: format!("{} vs. 3x3x3 Cube PR Average", event.pretty_name()),
+        y_desc: match event {
+            Event::Ev333fm => format!("{} average [moves]", event.pretty_name()),
+            _ => format!("{} average", event.pretty_name()),
+        },
+    }
+}
+
+/// Caption and axis areas shared by all plots.
+fn chart_builder<'a, 'b, DB: DrawingBackend>(
+    root_area: &'a DrawingArea<DB, Shift>,
+    title: &'b str,
+    scale: u32,
+) -> ChartBuilder<'a, 'b, DB> {
+    let mut builder = ChartBuilder::on(root_area);
+    builder
+        .margin_right(20 * scale)
+        .set_label_area_size(LabelAreaPosition::Left, 50 * scale)
+        .set_label_area_size(LabelAreaPosition::Bottom, 40 * scale)
+        .caption(title, ("sans-serif", 40 * scale));
+    builder
+}
+
+/// Range covering all values with some space around them.
+fn padded_range(values: impl Iterator<Item = f64> + Clone) -> std::ops::Range<f64> {
+    let lowest = values.clone().reduce(f64::min).unwrap_or(0.0);
+    let highest = values.reduce(f64::max).unwrap_or(0.0);
+    let padding = ((highest - lowest) * 0.05).max(0.5);
+    (lowest - padding).max(0.0)..highest + padding
+}
+
+fn draw_histogram<DB: DrawingBackend>(
+    pd: &HistogramData,
+    root_area: DrawingArea<DB, Shift>,
+    scale: u32,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let font_size = 12 * scale;
     root_area.fill(&GREY)?;
 
-    let mut ctx = ChartBuilder::on(&root_area)
-        .set_label_area_size(LabelAreaPosition::Left, 40)
-        .set_label_area_size(LabelAreaPosition::Bottom, 40)
-        .caption(&pd.title, ("sans-serif", 40))
-        .build_cartesian_2d((pd.lowest..pd.highest).into_segmented(), 0..pd.max_count)?;
+    let mut ctx = chart_builder(&root_area, &pd.title, scale).build_cartesian_2d(
+        pd.lowest..pd.lowest + pd.histogram_data.len() as f64 * pd.bin_width,
+        0..pd.max_count.max(1),
+    )?;
 
     ctx.configure_mesh()
+        .x_label_formatter(&|x| axis_label(*x, pd.time_axis))
         .x_desc(&pd.x_desc)
         .y_desc(&pd.y_desc)
+        .label_style(("sans-serif", font_size))
+        .axis_desc_style(("sans-serif", font_size))
         .draw()?;
 
-    ctx.draw_series((pd.lowest..).zip(pd.histogram_data.iter()).map(|(x, y)| {
-        let x0 = SegmentValue::Exact(x);
-        let x1 = SegmentValue::Exact(x + 1);
-        let mut bar = Rectangle::new([(x0, 0), (x1, *y)], BLUE.filled());
-        bar.set_margin(0, 0, 5, 5);
+    ctx.draw_series(pd.histogram_data.iter().enumerate().map(|(index, count)| {
+        let x0 = pd.lowest + index as f64 * pd.bin_width;
+        let mut bar = Rectangle::new([(x0, 0), (x0 + pd.bin_width, *count)], BLUE.filled());
+        bar.set_margin(0, 0, scale, scale);
         bar
     }))?;
 
+    if let Some(cutoff) = pd.cutoff {
+        let stroke = RED.stroke_width(3 * scale);
+        ctx.draw_series(LineSeries::new(
+            [0, pd.max_count.max(1)].map(|y| (cutoff, y)),
+            stroke,
+        ))?
+        .label("Cutoff")
+        .legend(move |(x, y)| PathElement::new([(x, y), (x + 20 * scale as i32, y)], stroke));
+        ctx.configure_series_labels()
+            .label_font(("sans-serif", font_size))
+            .legend_area_size(30 * scale)
+            .background_style(WHITE)
+            .border_style(BLACK)
+            .draw()?;
+    }
+
+    root_area.present()?;
+    Ok(())
+}
+
+fn draw_cumulative<DB: DrawingBackend>(
+    cd: &CumulativeData,
+    root_area: DrawingArea<DB, Shift>,
+    scale: u32,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let font_size = 12 * scale;
+    root_area.fill(&GREY)?;
+
+    let x_range = padded_range(cd.values.iter().chain(&cd.cutoff).copied());
+    let mut ctx = chart_builder(&root_area, &cd.title, scale)
+        .build_cartesian_2d(x_range.clone(), 0.0..100.0)?;
+
+    ctx.configure_mesh()
+        .x_label_formatter(&|x| axis_label(*x, cd.time_axis))
+        .y_label_formatter(&|y| format!("{:.0}%", y))
+        .x_desc(&cd.x_desc)
+        .y_desc("Participants")
+        .label_style(("sans-serif", font_size))
+        .axis_desc_style(("sans-serif", font_size))
+        .draw()?;
+
+    // step function, rising at every PR
+    let count = cd.values.len() as f64;
+    let mut steps = vec![(x_range.start, 0.0)];
+    for (index, value) in cd.values.iter().enumerate() {
+        steps.push((*value, index as f64 / count * 100.0));
+        steps.push((*value, (index + 1) as f64 / count * 100.0));
+    }
+    if let Some((_, percentage)) = steps.last().copied() {
+        steps.push((x_range.end, percentage));
+    }
+    ctx.draw_series(LineSeries::new(steps, BLUE.stroke_width(2 * scale)))?;
+
+    if let Some(cutoff) = cd.cutoff {
+        let stroke = RED.stroke_width(3 * scale);
+        ctx.draw_series(LineSeries::new([0.0, 100.0].map(|y| (cutoff, y)), stroke))?
+            .label("Cutoff")
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20 * scale as i32, y)], stroke));
+        ctx.configure_series_labels()
+            .position(SeriesLabelPosition::LowerRight)
+            .label_font(("sans-serif", font_size))
+            .legend_area_size(30 * scale)
+            .background_style(WHITE)
+            .border_style(BLACK)
+            .draw()?;
+    }
+
     root_area.present()?;
     Ok(())
 }
 
-pub fn plot(
-    competitors: &[Competitor],
-    event: &Event,
-    out_path: &PathBuf,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let plot_data = generate_plot_data(competitors, event);
-    generate_plot(&plot_data, out_path)
+fn draw_event_boxes<DB: DrawingBackend>(
+    boxes: &[EventBox],
+    root_area: DrawingArea<DB, Shift>,
+    scale: u32,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let font_size = 12 * scale;
+    root_area.fill(&GREY)?;
+
+    let y_range = padded_range(boxes.iter().flat_map(|event_box| {
+        event_box
+            .outliers
+            .iter()
+            .copied()
+            .chain([event_box.lower_whisker, event_box.upper_whisker])
+    }));
+    let mut ctx = chart_builder(&root_area, "PRs relative to the median of the event", scale)
+        // segmented ranges include their end
+        .build_cartesian_2d((0..boxes.len() - 1).into_segmented(), y_range)?;
+
+    ctx.configure_mesh()
+        .disable_x_mesh()
+        .x_labels(boxes.len())
+        .x_label_formatter(&|x| match x {
+            SegmentValue::CenterOf(index) => boxes
+                .get(*index)
+                .map(|event_box| event_box.event.code_name().to_owned())
+                .unwrap_or_default(),
+            _ => String::new(),
+        })
+        .y_label_formatter(&|y| format!("{:.1}", y))
+        .y_desc("PR / median PR")
+        .label_style(("sans-serif", font_size))
+        .axis_desc_style(("sans-serif", font_size))
+        .draw()?;
+
+    // leave a quarter of the space of every event free on both sides of its box
+    let margin = root_area.dim_in_pixel().0 / (4 * boxes.len().max(1) as u32);
+    let stroke = BLACK.stroke_width(scale);
+    for (index, event_box) in boxes.iter().enumerate() {
+        let center = |y| (SegmentValue::CenterOf(index), y);
+        ctx.draw_series(LineSeries::new(
+            [event_box.lower_whisker, event_box.upper_whisker].map(center),
+            stroke,
+        ))?;
+        let mut rectangle = Rectangle::new(
+            [
+                (SegmentValue::Exact(index), event_box.lower_quartile),
+                (SegmentValue::Exact(index + 1), event_box.upper_quartile),
+            ],
+            BLUE.filled(),
+        );
+        rectangle.set_margin(0, 0, margin, margin);
+        ctx.draw_series([rectangle])?;
+        let mut median = Rectangle::new(
+            [
+                (SegmentValue::Exact(index), event_box.median),
+                (SegmentValue::Exact(index + 1), event_box.median),
+            ],
+            WHITE.stroke_width(2 * scale),
+        );
+        median.set_margin(0, 0, margin, margin);
+        ctx.draw_series([median])?;
+        ctx.draw_series(
+            event_box
+                .outliers
+                .iter()
+                .map(|value| Circle::new(center(*value), 3 * scale, stroke)),
+        )?;
+    }
+
+    root_area.present()?;
+    Ok(())
+}
+
+fn draw_scatter<DB: DrawingBackend>(
+    sd: &ScatterData,
+    root_area: DrawingArea<DB, Shift>,
+    scale: u32,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let font_size = 12 * scale;
+    root_area.fill(&GREY)?;
+
+    let x_range = padded_range(sd.points.iter().map(|(x, _)| *x));
+    let y_range = padded_range(sd.points.iter().map(|(_, y)| *y));
+    let mut ctx =
+        chart_builder(&root_area, &sd.title, scale).build_cartesian_2d(x_range, y_range)?;
+
+    ctx.configure_mesh()
+        .x_label_formatter(&|x| axis_label(*x, true))
+        .y_label_formatter(&|y| axis_label(*y, sd.y_time_axis))
+        .x_desc("3x3x3 Cube average")
+        .y_desc(&sd.y_desc)
+        .label_style(("sans-serif", font_size))
+        .axis_desc_style(("sans-serif", font_size))
+        .draw()?;
+
+    ctx.draw_series(
+        sd.points
+            .iter()
+            .map(|point| Circle::new(*point, 3 * scale, BLUE.filled())),
+    )?;
+
+    root_area.present()?;
+    Ok(())
+}
+
+enum Chart {
+    Histogram(HistogramData),
+    Cumulative(CumulativeData),
+    EventBoxes(Vec<EventBox>),
+    Scatter(ScatterData),
+}
+
+fn draw_chart<DB: DrawingBackend>(
+    chart: &Chart,
+    root_area: DrawingArea<DB, Shift>,
+    scale: u32,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    match chart {
+        Chart::Histogram(data) => draw_histogram(data, root_area, scale),
+        Chart::Cumulative(data) => draw_cumulative(data, root_area, scale),
+        Chart::EventBoxes(boxes) => draw_event_boxes(boxes, root_area, scale),
+        Chart::Scatter(data) => draw_scatter(data, root_area, scale),
+    }
+}
+
+/// Image file format of the plots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlotFormat {
+    Png,
+    /// Embedded into the HTML pages instead of being saved as files
+    Svg,
+}
+
+impl FromStr for PlotFormat {
+    type Err = WCOError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "png" => Ok(PlotFormat::Png),
+            "svg" => Ok(PlotFormat::Svg),
+            _ => Err(WCOError::ParsingError(format!(
+                "Invalid plot format specified: \"{}\"",
+                s
+            ))),
+        }
+    }
+}
+
+pub struct PlotSettings {
+    pub format: PlotFormat,
+    /// Displayed size of the plots in pixels
+    pub width: u32,
+    pub height: u32,
+    /// Factor by which PNGs are rendered larger than they are displayed, for high-DPI screens
+    /// and printing
+    pub scale: u32,
+    /// Width of the histogram bins in seconds, moves or points, chosen by the Freedman–Diaconis
+    /// rule if not set
+    pub bin_width: Option<f64>,
+    /// Leave out results far slower or faster than the rest
+    pub clip_outliers: bool,
+}
+
+/// A plot ready to be shown on a page.
+pub enum Plot {
+    /// PNG file relative to the report directory, with its displayed size
+    File {
+        path: String,
+        width: u32,
+        height: u32,
+    },
+    /// SVG document
+    Svg(String),
+}
+
+/// Draws the chart with the backend of the plot format. PNGs are saved as `plots/<name>.png` in
+/// `report_dir`.
+fn render(
+    chart: &Chart,
+    settings: &PlotSettings,
+    report_dir: &Path,
+    name: &str,
+) -> Result<Plot, WCOError> {
+    let size = (settings.width, settings.height);
+    let result = match settings.format {
+        PlotFormat::Png => {
+            let file_name = format!("plots/{}.png", name);
+            let scale = settings.scale.max(1);
+            let out_path = report_dir.join(&file_name);
+            let root_area =
+                BitMapBackend::new(&out_path, (size.0 * scale, size.1 * scale)).into_drawing_area();
+            draw_chart(chart, root_area, scale).map(|_| Plot::File {
+                path: file_name,
+                width: size.0,
+                height: size.1,
+            })
+        }
+        PlotFormat::Svg => {
+            let mut svg = String::new();
+            let root_area = SVGBackend::with_string(&mut svg, size).into_drawing_area();
+            draw_chart(chart, root_area, 1).map(|_| Plot::Svg(svg))
+        }
+    };
+    result.map_err(|e| WCOError::PlottingError(e.to_string()))
+}
+
+/// Histogram of the PRs of an event.
+pub fn plot_histogram(
+    event_report: &EventReport,
+    settings: &PlotSettings,
+    report_dir: &Path,
+    name: &str,
+) -> Result<Plot, WCOError> {
+    let chart = Chart::Histogram(generate_histogram_data(event_report, settings));
+    render(&chart, settings, report_dir, name)
+}
+
+/// Percentage of the participants of an event with at most a certain PR.
+pub fn plot_cumulative(
+    event_report: &EventReport,
+    settings: &PlotSettings,
+    report_dir: &Path,
+    name: &str,
+) -> Result<Plot, WCOError> {
+    let chart = Chart::Cumulative(generate_cumulative_data(event_report));
+    render(&chart, settings, report_dir, name)
+}
+
+/// Box plots of the PRs of all events, relative to the median of each event. `None` if no event
+/// has PRs.
+pub fn plot_event_comparison(
+    report: &Report,
+    settings: &PlotSettings,
+    report_dir: &Path,
+    name: &str,
+) -> Result<Option<Plot>, WCOError> {
+    let boxes = generate_event_boxes(report);
+    if boxes.is_empty() {
+        return Ok(None);
+    }
+    render(&Chart::EventBoxes(boxes), settings, report_dir, name).map(Some)
+}
+
+/// PR averages of an event against the 3x3x3 PR averages of the same competitors. `None` for
+/// 3x3x3 itself, events without averages and if fewer than two participants have both averages.
+pub fn plot_scatter(
+    event_report: &EventReport,
+    settings: &PlotSettings,
+    report_dir: &Path,
+    name: &str,
+) -> Result<Option<Plot>, WCOError> {
+    if event_report.event == Event::Ev333 || !event_report.event.has_average() {
+        return Ok(None);
+    }
+    let data = generate_scatter_data(event_report);
+    if data.points.len() < 2 {
+        return Ok(None);
+    }
+    render(&Chart::Scatter(data), settings, report_dir, name).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datastructures::{Competition, Competitor, PersonalRecord, RecordType};
+
+    #[test]
+    fn interpolates_quantiles() {
+        let values = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(quantile(&values, 0.0), 1.0);
+        assert_eq!(quantile(&values, 0.25), 1.75);
+        assert_eq!(quantile(&values, 0.5), 2.5);
+        assert_eq!(quantile(&values, 1.0), 4.0);
+        assert_eq!(quantile(&[7.0], 0.5), 7.0);
+    }
+
+    #[test]
+    fn rounds_freedman_diaconis_width_to_nice_width() {
+        assert_eq!(freedman_diaconis_width(&[]), 1.0);
+        assert_eq!(freedman_diaconis_width(&[5.0]), 1.0);
+        assert_eq!(freedman_diaconis_width(&[5.0; 10]), 1.0);
+        // IQR 13 of 27 values: 2 * 13 / 3 = 8.7, closest to 10
+        let values: Vec<_> = (10..37).map(f64::from).collect();
+        assert_eq!(freedman_diaconis_width(&values), 10.0);
+        // IQR 1.3: 2 * 1.3 / 3 = 0.87, closest to 1
+        let values: Vec<_> = values.iter().map(|value| value / 10.0).collect();
+        assert_eq!(freedman_diaconis_width(&values), 1.0);
+    }
+
+    #[test]
+    fn computes_tukey_fences() {
+        assert_eq!(tukey_fences(&[1.0, 2.0, 3.0]), None);
+        assert_eq!(tukey_fences(&[1.0, 2.0, 3.0, 4.0]), Some((-0.5, 5.5)));
+    }
+
+    #[test]
+    fn labels_axis_like_the_tables() {
+        assert_eq!(axis_label(65.0, true), "1:05");
+        assert_eq!(axis_label(9.5, true), "9.50");
+        assert_eq!(axis_label(12.0, true), "12");
+        assert_eq!(axis_label(-1.0, true), "0");
+        assert_eq!(axis_label(30.0, false), "30");
+        assert_eq!(axis_label(27.5, false), "27.5");
+    }
+
+    fn competition() -> Competition {
+        Competition {
+            name: "Test Open 2024".to_owned(),
+            start_date: None,
+            end_date: None,
+            events: vec![],
+            schedule: vec![],
+        }
+    }
+
+    /// Competitors with the given 3x3x3 averages in seconds.
+    fn competitors(averages: &[f64]) -> Vec<Competitor> {
+        averages
+            .iter()
+            .enumerate()
+            .map(|(index, average)| {
+                let mut competitor = Competitor::new(
+                    format!("Competitor {}", index),
+                    Some(format!("2020TEST{:02}", index)),
+                    vec![Event::Ev333],
+                );
+                let mut record = PersonalRecord::default();
+                let value = ResultValue::Time(Duration::from_secs_f64(*average));
+                record.set(RecordType::Average, value, None);
+                competitor.personal_records.insert(Event::Ev333, record);
+                competitor
+            })
+            .collect()
+    }
+
+    fn settings(bin_width: Option<f64>) -> PlotSettings {
+        PlotSettings {
+            format: PlotFormat::Svg,
+            width: 800,
+            height: 400,
+            scale: 1,
+            bin_width,
+            clip_outliers: false,
+        }
+    }
+
+    fn histogram(averages: &[f64], bin_width: Option<f64>) -> HistogramData {
+        let competition = competition();
+        let competitors = competitors(averages);
+        let report = Report::new(
+            &competition,
+            &competitors,
+            &[(Event::Ev333, RecordType::Average)],
+        );
+        generate_histogram_data(&report.events[0], &settings(bin_width))
+    }
+
+    #[test]
+    fn bins_values_with_given_width() {
+        let data = histogram(&[10.2, 10.4, 11.0, 12.9], Some(1.0));
+        assert_eq!(data.lowest, 10.0);
+        assert_eq!(data.histogram_data, [2, 1, 1]);
+        assert_eq!(data.max_count, 2);
+    }
+
+    #[test]
+    fn widens_given_bin_widths_leading_to_too_many_bins() {
+        let averages = [8.0, 12.0, 60.0];
+        assert_eq!(histogram(&averages, Some(0.5)).bin_width, 0.5);
+        // 5201 bins of 0.01 seconds, 521 of the next nice width
+        let data = histogram(&averages, Some(0.01));
+        assert_eq!(data.bin_width, 0.1);
+        assert_eq!(data.histogram_data.len(), 521);
+    }
+
+    #[test]
+    fn event_boxes_are_relative_to_their_median() {
+        let competition = competition();
+        // even count, the median is between 10 and 14
+        let competitors = competitors(&[8.0, 10.0, 14.0, 40.0]);
+        let report = Report::new(
+            &competition,
+            &competitors,
+            &[(Event::Ev333, RecordType::Average)],
+        );
+        let boxes = generate_event_boxes(&report);
+        assert_eq!(boxes.len(), 1);
+        let event_box = &boxes[0];
+        let assert_close = |value: f64, expected: f64| {
+            assert!((value - expected).abs() < 1e-9, "{} != {}", value, expected)
+        };
+        assert_close(event_box.median, 1.0);
+        assert_close(event_box.lower_whisker, 8.0 / 12.0);
+        assert_close(event_box.upper_quartile, 20.5 / 12.0);
+        assert_close(event_box.upper_whisker, 14.0 / 12.0);
+        assert_eq!(event_box.outliers.len(), 1);
+        assert_close(event_box.outliers[0], 40.0 / 12.0);
+    }
+
+    #[test]
+    fn widens_automatic_bins_for_far_outliers() {
+        let mut averages: Vec<_> = (0..30).map(|index| 10.0 + index as f64 * 0.05).collect();
+        averages.push(3000.0);
+        let data = histogram(&averages, None);
+        assert!(data.histogram_data.len() <= MAX_BIN_COUNT);
+        assert_eq!(data.histogram_data.iter().sum::<u64>(), 31);
+    }
 }
@@ -0,0 +1,244 @@
+use crate::datastructures::{AdvancementCondition, Competitor, Event, RecordType, ResultValue};
+use crate::report::EventReport;
+
+/// Expected participants of one round, ordered by their expected placing.
+pub struct RoundProjection<'a> {
+    pub competitors: Vec<&'a Competitor>,
+    /// Number of competitors expected to advance to the next round, `None` for the final
+    pub advancing: Option<usize>,
+}
+
+/// Naive prediction of an event's rounds: competitors are expected to place in the order of
+/// their PRs, competitors without a PR are expected to place last.
+pub struct EventPrediction<'a> {
+    pub rounds: Vec<RoundProjection<'a>>,
+    ranking: RecordType,
+    event: Event,
+}
+
+impl<'a> EventPrediction<'a> {
+    pub fn podium(&self) -> Vec<&'a Competitor> {
+        self.finalists()
+            .iter()
+            .filter(|comp| self.has_record(comp))
+            .take(3)
+            .copied()
+            .collect()
+    }
+
+    pub fn finalists(&self) -> &[&'a Competitor] {
+        self.rounds
+            .last()
+            .map(|round| round.competitors.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn has_record(&self, competitor: &Competitor) -> bool {
+        competitor
+            .personal_records
+            .get(&self.event)
+            .is_some_and(|pr| pr.get(self.ranking).is_some())
+    }
+}
+
+/// Predicts who takes part in which round of the event, starting from the participants in the
+/// order of the report. Without round information, the event is treated as a single round.
+pub fn predict_event<'a>(event_report: &EventReport<'a>) -> EventPrediction<'a> {
+    let event = &event_report.event;
+    let ranking = event_report.ranking;
+    let rounds = event_report.rounds;
+    let mut participants: Vec<_> = event_report.participants().collect();
+
+    let mut projections = vec![];
+    let round_count = rounds.len().max(1);
+    for round_index in 0..round_count {
+        let advancing = rounds
+            .get(round_index)
+            .and_then(|round| round.advancement)
+            .filter(|_| round_index + 1 < round_count)
+            .map(|condition| advancing_count(&condition, &participants, event, ranking));
+        let next_participants = participants[..advancing.unwrap_or(0)].to_vec();
+        projections.push(RoundProjection {
+            competitors: participants,
+            advancing,
+        });
+        participants = next_participants;
+    }
+    EventPrediction {
+        rounds: projections,
+        ranking,
+        event: *event,
+    }
+}
+
+fn advancing_count(
+    condition: &AdvancementCondition,
+    participants: &[&Competitor],
+    event: &Event,
+    ranking: RecordType,
+) -> usize {
+    let count = participants.len();
+    let advancing = match condition {
+        AdvancementCondition::Ranking(level) => *level as usize,
+        AdvancementCondition::Percent(level) => count * *level as usize / 100,
+        AdvancementCondition::AttemptResult(level) => {
+            let limit = ResultValue::from_wca_value(*event, *level, ranking == RecordType::Average);
+            participants
+                .iter()
+                .filter_map(|comp| comp.personal_records.get(event))
+                .filter_map(|pr| pr.get(ranking))
+                .filter(|value| **value < limit)
+                .count()
+        }
+    };
+    // regulation 9p1: at least 25% of the competitors are eliminated in every round
+    advancing.min(count * 3 / 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::datastructures::{
+        Competition, CompetitionEvent, PersonalRecord, Round, RoundFormat,
+    };
+    use crate::report::Report;
+
+    fn competitor(name: &str, single: Option<u64>, average: Option<u64>) -> Competitor {
+        let mut competitor = Competitor::new(
+            name.to_owned(),
+            Some(format!("2020{}01", name.to_uppercase())),
+            vec![Event::Ev333],
+        );
+        let mut record = PersonalRecord::default();
+        for (record_type, seconds) in [(RecordType::Single, single), (RecordType::Average, average)]
+        {
+            if let Some(seconds) = seconds {
+                let value = ResultValue::Time(Duration::from_secs(seconds));
+                record.set(record_type, value, None);
+            }
+        }
+        if single.is_some() || average.is_some() {
+            competitor.personal_records.insert(Event::Ev333, record);
+        }
+        competitor
+    }
+
+    /// Ten competitors "c0" to "c9" with averages of 10 to 19 seconds.
+    fn field() -> Vec<Competitor> {
+        (0..10)
+            .map(|i| competitor(&format!("c{}", i), Some(9 + i), Some(10 + i)))
+            .collect()
+    }
+
+    fn competition(advancements: &[Option<AdvancementCondition>]) -> Competition {
+        let rounds = advancements
+            .iter()
+            .map(|advancement| Round {
+                format: RoundFormat::AverageOf5,
+                advancement: *advancement,
+                cutoff: None,
+                time_limit: None,
+            })
+            .collect();
+        Competition {
+            name: "Test Open 2024".to_owned(),
+            start_date: None,
+            end_date: None,
+            events: vec![CompetitionEvent {
+                event: Event::Ev333,
+                rounds,
+            }],
+            schedule: vec![],
+        }
+    }
+
+    fn names<'a>(competitors: &[&'a Competitor]) -> Vec<&'a str> {
+        competitors.iter().map(|comp| comp.name.as_str()).collect()
+    }
+
+    fn count(condition: AdvancementCondition, competitors: &[Competitor]) -> usize {
+        let participants: Vec<_> = competitors.iter().collect();
+        advancing_count(
+            &condition,
+            &participants,
+            &Event::Ev333,
+            RecordType::Average,
+        )
+    }
+
+    #[test]
+    fn counts_advancing_competitors() {
+        let competitors = field();
+        assert_eq!(count(AdvancementCondition::Ranking(5), &competitors), 5);
+        assert_eq!(count(AdvancementCondition::Percent(50), &competitors), 5);
+        // percentages are rounded down
+        assert_eq!(count(AdvancementCondition::Percent(25), &competitors), 2);
+        // an average better than 13.00
+        assert_eq!(
+            count(AdvancementCondition::AttemptResult(1300), &competitors),
+            3
+        );
+    }
+
+    #[test]
+    fn eliminates_at_least_a_quarter() {
+        let competitors = field();
+        assert_eq!(count(AdvancementCondition::Ranking(9), &competitors), 7);
+        assert_eq!(count(AdvancementCondition::Percent(100), &competitors), 7);
+        assert_eq!(
+            count(AdvancementCondition::AttemptResult(9000), &competitors),
+            7
+        );
+    }
+
+    #[test]
+    fn projects_rounds_from_the_report_ranking() {
+        let competition = competition(&[
+            Some(AdvancementCondition::Percent(60)),
+            Some(AdvancementCondition::Ranking(3)),
+            None,
+        ]);
+        let mut competitors = field();
+        competitors.reverse();
+        let report = Report::new(
+            &competition,
+            &competitors,
+            &[(Event::Ev333, RecordType::Average)],
+        );
+        let prediction = predict_event(&report.events[0]);
+
+        let sizes: Vec<_> = prediction
+            .rounds
+            .iter()
+            .map(|round| (round.competitors.len(), round.advancing))
+            .collect();
+        assert_eq!(sizes, [(10, Some(6)), (6, Some(3)), (3, None)]);
+        assert_eq!(names(prediction.finalists()), ["c0", "c1", "c2"]);
+    }
+
+    #[test]
+    fn podium_skips_competitors_without_a_record_of_the_ranking() {
+        let competition = competition(&[]);
+        let competitors = vec![
+            Competitor::new("Newcomer".to_owned(), None, vec![Event::Ev333]),
+            competitor("single", Some(5), None),
+            competitor("slow", Some(20), Some(25)),
+            competitor("fast", Some(8), Some(10)),
+        ];
+        let report = Report::new(
+            &competition,
+            &competitors,
+            &[(Event::Ev333, RecordType::Average)],
+        );
+        let prediction = predict_event(&report.events[0]);
+
+        // without rounds, everyone is in the final
+        assert_eq!(
+            names(prediction.finalists()),
+            ["fast", "slow", "single", "Newcomer"]
+        );
+        assert_eq!(names(&prediction.podium()), ["fast", "slow"]);
+    }
+}
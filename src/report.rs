@@ -0,0 +1,338 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::datastructures::{Competition, Competitor, Event, RecordType, ResultValue, Round};
+use crate::wcoerror::WCOError;
+
+/// Everything the output formats show about a competition, computed once so that all formats
+/// agree on rankings and counts.
+pub struct Report<'a> {
+    pub competition: &'a Competition,
+    pub competitors: &'a [Competitor],
+    /// Registered events in their usual order
+    pub events: Vec<EventReport<'a>>,
+}
+
+impl<'a> Report<'a> {
+    pub fn new(
+        competition: &'a Competition,
+        competitors: &'a [Competitor],
+        events: &[(Event, RecordType)],
+    ) -> Report<'a> {
+        let mut events: Vec<_> = events
+            .iter()
+            .map(|(event, ranking)| EventReport::new(competition, competitors, *event, *ranking))
+            .collect();
+        events.sort_by_key(|event_report| event_report.event);
+        Report {
+            competition,
+            competitors,
+            events,
+        }
+    }
+
+    pub fn returner_count(&self) -> usize {
+        self.competitors
+            .iter()
+            .filter(|comp| comp.wca_id.is_some())
+            .count()
+    }
+
+    pub fn newcomer_count(&self) -> usize {
+        self.competitors.len() - self.returner_count()
+    }
+}
+
+/// Participants of one event, split into the categories shown on the event page.
+pub struct EventReport<'a> {
+    pub event: Event,
+    pub ranking: RecordType,
+    /// Rounds from the WCIF, empty if unknown
+    pub rounds: &'a [Round],
+    /// Participants with a PR of the event, ordered by `ranking`
+    pub ranked: Vec<&'a Competitor>,
+    /// Participants with a WCA ID, but without a PR of the event
+    pub without_record: Vec<&'a Competitor>,
+    /// Participants whose PRs could not be retrieved
+    pub failed: Vec<&'a Competitor>,
+    pub newcomers: Vec<&'a Competitor>,
+    pub statistics: Option<Statistics>,
+}
+
+impl<'a> EventReport<'a> {
+    fn new(
+        competition: &'a Competition,
+        competitors: &'a [Competitor],
+        event: Event,
+        ranking: RecordType,
+    ) -> EventReport<'a> {
+        let mut ranked = vec![];
+        let mut without_record = vec![];
+        let mut failed = vec![];
+        let mut newcomers = vec![];
+        for comp in competitors
+            .iter()
+            .filter(|comp| comp.events.contains(&event))
+        {
+            match comp {
+                Competitor { wca_id: None, .. } => newcomers.push(comp),
+                Competitor {
+                    retrieval_error: Some(_),
+                    ..
+                } => failed.push(comp),
+                _ if !comp.personal_records.contains_key(&event) => without_record.push(comp),
+                _ => ranked.push(comp),
+            }
+        }
+        ranked.sort_by_key(|comp| comp.personal_records[&event].ranking_key(ranking));
+        let rounds = competition
+            .event(&event)
+            .map(|comp_event| comp_event.rounds.as_slice())
+            .unwrap_or_default();
+        let mut event_report = EventReport {
+            event,
+            ranking,
+            rounds,
+            ranked,
+            without_record,
+            failed,
+            newcomers,
+            statistics: None,
+        };
+        event_report.statistics = Statistics::new(event_report.ranked_values());
+        event_report
+    }
+
+    pub fn participant_count(&self) -> usize {
+        self.ranked.len() + self.without_record.len() + self.failed.len() + self.newcomers.len()
+    }
+
+    /// All participants in the order of the event tables. Only the first `ranked.len()` of them
+    /// have a rank.
+    pub fn participants(&self) -> impl Iterator<Item = &'a Competitor> + '_ {
+        self.ranked
+            .iter()
+            .chain(&self.without_record)
+            .chain(&self.failed)
+            .chain(&self.newcomers)
+            .copied()
+    }
+
+    /// PRs of the ranking type of the ranked participants, best first.
+    pub fn ranked_values(&self) -> Vec<ResultValue> {
+        self.ranked
+            .iter()
+            .filter_map(|comp| {
+                comp.personal_records[&self.event]
+                    .get(self.ranking)
+                    .copied()
+            })
+            .collect()
+    }
+}
+
+/// Distribution of the participants' PRs of the ranking type.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Statistics {
+    pub best: ResultValue,
+    pub median: ResultValue,
+    pub worst: ResultValue,
+}
+
+impl Statistics {
+    fn new(mut values: Vec<ResultValue>) -> Option<Statistics> {
+        values.sort();
+        Some(Statistics {
+            best: *values.first()?,
+            median: values[values.len() / 2],
+            worst: *values.last()?,
+        })
+    }
+}
+
+/// An output format of the report.
+pub trait Renderer {
+    /// Writes the files of this format to `report_dir`.
+    fn render(&self, report: &Report, report_dir: &Path) -> Result<(), WCOError>;
+
+    /// Whether `render` writes any files. The report directory is only created if one of the
+    /// requested formats does.
+    fn writes_files(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::datastructures::PersonalRecord;
+
+    fn time(seconds: u64) -> ResultValue {
+        ResultValue::Time(Duration::from_secs(seconds))
+    }
+
+    fn competition() -> Competition {
+        Competition {
+            name: "Test Open 2024".to_owned(),
+            start_date: None,
+            end_date: None,
+            events: vec![],
+            schedule: vec![],
+        }
+    }
+
+    fn competitor(name: &str, wca_id: Option<&str>, events: &[Event]) -> Competitor {
+        Competitor::new(name.to_owned(), wca_id.map(str::to_owned), events.to_vec())
+    }
+
+    fn with_record(mut competitor: Competitor, single: u64, average: Option<u64>) -> Competitor {
+        let mut record = PersonalRecord::default();
+        record.set(RecordType::Single, time(single), None);
+        if let Some(average) = average {
+            record.set(RecordType::Average, time(average), None);
+        }
+        competitor.personal_records.insert(Event::Ev333, record);
+        competitor
+    }
+
+    fn competitors() -> Vec<Competitor> {
+        let events = [Event::Ev333];
+        let mut failed = competitor("Failed", Some("2016FAIL01"), &events);
+        failed.retrieval_error = Some("timeout".to_owned());
+        vec![
+            competitor("Newcomer", None, &events),
+            with_record(
+                competitor("Slow", Some("2015SLOW01"), &events),
+                20,
+                Some(25),
+            ),
+            failed,
+            competitor("No 3x3x3", Some("2017NONE01"), &events),
+            with_record(competitor("Fast", Some("2014FAST01"), &events), 9, Some(11)),
+            with_record(
+                competitor("Single only", Some("2018SING01"), &events),
+                7,
+                None,
+            ),
+            competitor("Other event", Some("2019OTHE01"), &[Event::Ev222]),
+        ]
+    }
+
+    fn names<'a>(competitors: &[&'a Competitor]) -> Vec<&'a str> {
+        competitors.iter().map(|comp| comp.name.as_str()).collect()
+    }
+
+    #[test]
+    fn partitions_participants() {
+        let competition = competition();
+        let competitors = competitors();
+        let report = Report::new(
+            &competition,
+            &competitors,
+            &[(Event::Ev333, RecordType::Average)],
+        );
+        let event_report = &report.events[0];
+        assert_eq!(names(&event_report.ranked).len(), 3);
+        assert_eq!(names(&event_report.without_record), ["No 3x3x3"]);
+        assert_eq!(names(&event_report.failed), ["Failed"]);
+        assert_eq!(names(&event_report.newcomers), ["Newcomer"]);
+        assert_eq!(event_report.participant_count(), 6);
+        assert_eq!(report.returner_count(), 6);
+        assert_eq!(report.newcomer_count(), 1);
+    }
+
+    #[test]
+    fn orders_by_ranking_type_then_other_type() {
+        let competition = competition();
+        let competitors = competitors();
+        let by_average = Report::new(
+            &competition,
+            &competitors,
+            &[(Event::Ev333, RecordType::Average)],
+        );
+        // competitors without an average are ranked behind all others
+        assert_eq!(
+            names(&by_average.events[0].ranked),
+            ["Fast", "Slow", "Single only"]
+        );
+        let by_single = Report::new(
+            &competition,
+            &competitors,
+            &[(Event::Ev333, RecordType::Single)],
+        );
+        assert_eq!(
+            names(&by_single.events[0].ranked),
+            ["Single only", "Fast", "Slow"]
+        );
+
+        let participants: Vec<_> = by_single.events[0].participants().collect();
+        assert_eq!(
+            names(&participants),
+            [
+                "Single only",
+                "Fast",
+                "Slow",
+                "No 3x3x3",
+                "Failed",
+                "Newcomer"
+            ]
+        );
+        assert_eq!(
+            by_single.events[0].ranked_values(),
+            [time(7), time(9), time(20)]
+        );
+        // the missing average is left out
+        assert_eq!(by_average.events[0].ranked_values(), [time(11), time(25)]);
+    }
+
+    #[test]
+    fn orders_events() {
+        let competition = competition();
+        let competitors = competitors();
+        let report = Report::new(
+            &competition,
+            &competitors,
+            &[
+                (Event::Ev222, RecordType::Average),
+                (Event::Ev333, RecordType::Average),
+            ],
+        );
+        let events: Vec<_> = report.events.iter().map(|ev| ev.event).collect();
+        assert_eq!(events, [Event::Ev333, Event::Ev222]);
+        assert!(report.events[1].ranked.is_empty());
+        assert_eq!(report.events[1].statistics, None);
+    }
+
+    #[test]
+    fn statistics_of_empty_odd_and_even_counts() {
+        assert_eq!(Statistics::new(vec![]), None);
+        assert_eq!(
+            Statistics::new(vec![time(5)]),
+            Some(Statistics {
+                best: time(5),
+                median: time(5),
+                worst: time(5),
+            })
+        );
+        assert_eq!(
+            Statistics::new(vec![time(9), time(3), time(5)]),
+            Some(Statistics {
+                best: time(3),
+                median: time(5),
+                worst: time(9),
+            })
+        );
+        // the upper of the two middle values, PRs are not averaged
+        assert_eq!(
+            Statistics::new(vec![time(9), time(3), time(5), time(7)]),
+            Some(Statistics {
+                best: time(3),
+                median: time(7),
+                worst: time(9),
+            })
+        );
+    }
+}
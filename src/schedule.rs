@@ -0,0 +1,374 @@
+use std::time::Duration;
+
+use crate::datastructures::{Competition, Cutoff, Event, ScheduledRound};
+use crate::prediction::predict_event;
+use crate::report::{EventReport, Report};
+use crate::simulation::{cutoff_chance, expected_attempt};
+
+/// Time per attempt besides solving: inspection, scrambling, handing over the puzzle
+pub const ATTEMPT_OVERHEAD: Duration = Duration::from_secs(45);
+/// Time to call up the next group and seat its competitors
+pub const GROUP_CHANGEOVER: Duration = Duration::from_secs(180);
+/// Assumed attempt duration if none of a round's competitors has any result
+const UNKNOWN_ATTEMPT: Duration = Duration::from_secs(60);
+/// Duration of an attempt in events where all competitors solve at the same time
+const FIXED_ATTEMPT: Duration = Duration::from_secs(3600);
+
+/// Expected course of a scheduled round.
+pub struct RoundEstimate<'a> {
+    pub scheduled: &'a ScheduledRound,
+    /// Expected number of competitors in the round
+    pub competitors: usize,
+    pub groups: usize,
+    /// Median of the competitors' expected attempt durations
+    pub typical_attempt: Option<Duration>,
+    pub cutoff: Option<Cutoff>,
+    /// Expected number of competitors making the cutoff
+    pub making_cutoff: Option<f64>,
+    pub estimated: Duration,
+}
+
+impl RoundEstimate<'_> {
+    pub fn overruns(&self) -> bool {
+        self.estimated > self.scheduled.duration()
+    }
+}
+
+/// Estimates how long every scheduled round takes. Competitors are assumed to be spread evenly
+/// across a round's groups, a group ends when its slowest competitor has finished all attempts.
+/// Competitors expected to miss the cutoff only do the cutoff attempts.
+///
+/// The number of stations is not taken into account: the WCIF does not contain it, and
+/// organizers choose the number of groups so that every competitor of a group has a station.
+pub fn estimate_schedule<'a>(report: &Report<'a>) -> Vec<RoundEstimate<'a>> {
+    report
+        .competition
+        .schedule
+        .iter()
+        .map(|scheduled| {
+            let event_report = report
+                .events
+                .iter()
+                .find(|event_report| event_report.event == scheduled.event);
+            estimate_round(report.competition, event_report, scheduled)
+        })
+        .collect()
+}
+
+/// Estimates a round of the event of `event_report`, which is `None` if nobody is registered for
+/// the event.
+fn estimate_round<'a>(
+    competition: &Competition,
+    event_report: Option<&EventReport>,
+    scheduled: &'a ScheduledRound,
+) -> RoundEstimate<'a> {
+    let event = scheduled.event;
+    let rounds = competition
+        .event(&event)
+        .map(|comp_event| comp_event.rounds.as_slice())
+        .unwrap_or_default();
+    let round_index = scheduled.round.saturating_sub(1);
+    let round = rounds.get(round_index);
+    let format = round.map_or(event.default_round_format(), |round| round.format);
+    let cutoff = round.and_then(|round| round.cutoff);
+    let time_limit = round
+        .and_then(|round| round.time_limit)
+        .filter(|limit| !limit.cumulative)
+        .map(|limit| limit.time);
+    let prediction = event_report.map(predict_event);
+    let participants = prediction
+        .as_ref()
+        .and_then(|prediction| prediction.rounds.get(round_index))
+        .map(|projection| projection.competitors.as_slice())
+        .unwrap_or_default();
+    let making_cutoff = cutoff.map(|cutoff| {
+        participants
+            .iter()
+            .map(|comp| cutoff_chance(comp, &event, &cutoff))
+            .sum()
+    });
+
+    if matches!(event, Event::Ev333fm | Event::Ev333mbf) {
+        let attempts = match scheduled.attempt {
+            Some(_) => 1,
+            None => format.attempt_count(),
+        };
+        return RoundEstimate {
+            scheduled,
+            competitors: participants.len(),
+            groups: 1,
+            typical_attempt: Some(FIXED_ATTEMPT),
+            cutoff,
+            making_cutoff,
+            estimated: FIXED_ATTEMPT * attempts as u32,
+        };
+    }
+
+    let expected_attempts: Vec<_> = participants
+        .iter()
+        .map(|comp| {
+            let attempt = Duration::from_millis(expected_attempt(comp, &event)? * 10);
+            Some(time_limit.map_or(attempt, |limit| attempt.min(limit)))
+        })
+        .collect();
+    let mut known_attempts: Vec<_> = expected_attempts.iter().flatten().copied().collect();
+    known_attempts.sort();
+    let typical_attempt = known_attempts.get(known_attempts.len() / 2).copied();
+
+    let mut competitor_durations: Vec<_> = participants
+        .iter()
+        .zip(&expected_attempts)
+        .map(|(comp, attempt)| {
+            let attempt = attempt.or(typical_attempt).unwrap_or(UNKNOWN_ATTEMPT);
+            let attempt_count = match &cutoff {
+                Some(cutoff) => {
+                    let cutoff_attempts = cutoff.attempts as f64;
+                    let remaining = format.attempt_count() as f64 - cutoff_attempts;
+                    cutoff_attempts + cutoff_chance(comp, &event, cutoff) * remaining
+                }
+                None => format.attempt_count() as f64,
+            };
+            (attempt + ATTEMPT_OVERHEAD).mul_f64(attempt_count)
+        })
+        .collect();
+    competitor_durations.sort_by(|a, b| b.cmp(a));
+
+    // the slowest competitors are dealt to the groups first, one each
+    let groups = scheduled.group_activity_ids.len().max(1);
+    let estimated = (0..groups)
+        .map(|group| {
+            let slowest = competitor_durations.get(group).copied().unwrap_or_default();
+            slowest + GROUP_CHANGEOVER
+        })
+        .sum();
+    RoundEstimate {
+        scheduled,
+        competitors: participants.len(),
+        groups,
+        typical_attempt,
+        cutoff,
+        making_cutoff,
+        estimated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeDelta, Utc};
+
+    use super::*;
+    use crate::datastructures::{
+        AdvancementCondition, CompetitionEvent, Competitor, PersonalRecord, RecordType,
+        ResultValue, Round, RoundFormat,
+    };
+
+    const START: &str = "2024-05-04T10:00:00Z";
+
+    /// Competitor whose recent attempts all took `seconds`.
+    fn competitor(name: &str, event: Event, seconds: u64) -> Competitor {
+        let mut competitor = Competitor::new(
+            name.to_owned(),
+            Some(format!("2020{}01", name.to_uppercase())),
+            vec![event],
+        );
+        let attempt = ResultValue::Time(Duration::from_secs(seconds));
+        competitor
+            .recent_attempts
+            .insert(event, vec![Some(attempt); 5]);
+        competitor
+    }
+
+    fn round(format: RoundFormat, cutoff: Option<Cutoff>) -> Round {
+        Round {
+            format,
+            advancement: None,
+            cutoff,
+            time_limit: None,
+        }
+    }
+
+    fn scheduled(
+        event: Event,
+        attempt: Option<usize>,
+        minutes: i64,
+        groups: u64,
+    ) -> ScheduledRound {
+        let start = DateTime::parse_from_rfc3339(START)
+            .unwrap()
+            .with_timezone(&Utc);
+        ScheduledRound {
+            activity_id: 1,
+            event,
+            round: 1,
+            attempt,
+            room: "Main".to_owned(),
+            start,
+            end: start + TimeDelta::minutes(minutes),
+            timezone: None,
+            group_activity_ids: (10..10 + groups).collect(),
+        }
+    }
+
+    fn estimate(
+        round: Round,
+        schedule: Vec<ScheduledRound>,
+        competitors: &[Competitor],
+    ) -> Vec<(Duration, bool)> {
+        estimate_rounds(vec![round], schedule, competitors, RecordType::Average)
+    }
+
+    fn estimate_rounds(
+        rounds: Vec<Round>,
+        schedule: Vec<ScheduledRound>,
+        competitors: &[Competitor],
+        ranking: RecordType,
+    ) -> Vec<(Duration, bool)> {
+        let event = schedule[0].event;
+        let competition = Competition {
+            name: "Test Open 2024".to_owned(),
+            start_date: None,
+            end_date: None,
+            events: vec![CompetitionEvent { event, rounds }],
+            schedule,
+        };
+        let report = Report::new(&competition, competitors, &[(event, ranking)]);
+        estimate_schedule(&report)
+            .iter()
+            .map(|estimate| (estimate.estimated, estimate.overruns()))
+            .collect()
+    }
+
+    fn cutoff(attempts: u32, seconds: u64) -> Option<Cutoff> {
+        Some(Cutoff {
+            attempts,
+            result: ResultValue::Time(Duration::from_secs(seconds)),
+        })
+    }
+
+    #[test]
+    fn groups_end_with_their_slowest_competitor() {
+        let competitors = [
+            competitor("fast", Event::Ev333, 10),
+            competitor("medium", Event::Ev333, 15),
+            competitor("slow", Event::Ev333, 20),
+        ];
+        let attempts = |seconds| (Duration::from_secs(seconds) + ATTEMPT_OVERHEAD) * 5;
+        let estimates = estimate(
+            round(RoundFormat::AverageOf5, None),
+            vec![scheduled(Event::Ev333, None, 60, 2)],
+            &competitors,
+        );
+        let expected = attempts(20) + attempts(15) + GROUP_CHANGEOVER * 2;
+        assert_eq!(estimates[0].0, expected);
+    }
+
+    #[test]
+    fn competitors_missing_the_cutoff_only_do_the_cutoff_attempts() {
+        let competitors = [competitor("a", Event::Ev333, 10)];
+        let schedule = || vec![scheduled(Event::Ev333, None, 60, 1)];
+        let attempt = Duration::from_secs(10) + ATTEMPT_OVERHEAD;
+
+        let missed = estimate(
+            round(RoundFormat::AverageOf5, cutoff(2, 5)),
+            schedule(),
+            &competitors,
+        );
+        assert_eq!(missed[0].0, attempt * 2 + GROUP_CHANGEOVER);
+        let made = estimate(
+            round(RoundFormat::AverageOf5, cutoff(2, 20)),
+            schedule(),
+            &competitors,
+        );
+        assert_eq!(made[0].0, attempt * 5 + GROUP_CHANGEOVER);
+    }
+
+    #[test]
+    fn fewest_moves_and_multi_blind_attempts_take_an_hour() {
+        let hours = |hours: u32| Duration::from_secs(3600) * hours;
+        let fmc = [competitor("a", Event::Ev333fm, 30)];
+        // all attempts in one slot or each attempt in its own slot
+        let together = estimate(
+            round(RoundFormat::MeanOf3, None),
+            vec![scheduled(Event::Ev333fm, None, 180, 2)],
+            &fmc,
+        );
+        assert_eq!(together, [(hours(3), false)]);
+        let separate = estimate(
+            round(RoundFormat::MeanOf3, None),
+            (1..=3)
+                .map(|attempt| scheduled(Event::Ev333fm, Some(attempt), 60, 0))
+                .collect(),
+            &fmc,
+        );
+        assert_eq!(separate, [(hours(1), false); 3]);
+
+        let mbf = [competitor("a", Event::Ev333mbf, 3000)];
+        let estimates = estimate(
+            round(RoundFormat::BestOf(2), None),
+            vec![scheduled(Event::Ev333mbf, None, 60, 1)],
+            &mbf,
+        );
+        assert_eq!(estimates, [(hours(2), true)]);
+    }
+
+    #[test]
+    fn predicts_later_rounds_with_the_report_ranking() {
+        let with_record = |mut competitor: Competitor, single: u64, average: u64| {
+            let mut record = PersonalRecord::default();
+            record.set(
+                RecordType::Single,
+                ResultValue::Time(Duration::from_secs(single)),
+                None,
+            );
+            record.set(
+                RecordType::Average,
+                ResultValue::Time(Duration::from_secs(average)),
+                None,
+            );
+            competitor.personal_records.insert(Event::Ev333, record);
+            competitor
+        };
+        let competitors = [
+            with_record(competitor("lucky", Event::Ev333, 10), 5, 20),
+            with_record(competitor("steady", Event::Ev333, 30), 8, 12),
+        ];
+        let final_estimate = |ranking| {
+            let mut first_round = round(RoundFormat::AverageOf5, None);
+            first_round.advancement = Some(AdvancementCondition::Ranking(1));
+            let mut final_round = scheduled(Event::Ev333, None, 60, 1);
+            final_round.round = 2;
+            estimate_rounds(
+                vec![first_round, round(RoundFormat::AverageOf5, None)],
+                vec![final_round],
+                &competitors,
+                ranking,
+            )[0]
+            .0
+        };
+        let attempts = |seconds| (Duration::from_secs(seconds) + ATTEMPT_OVERHEAD) * 5;
+        assert_eq!(
+            final_estimate(RecordType::Single),
+            attempts(10) + GROUP_CHANGEOVER
+        );
+        assert_eq!(
+            final_estimate(RecordType::Average),
+            attempts(30) + GROUP_CHANGEOVER
+        );
+    }
+
+    #[test]
+    fn flags_rounds_longer_than_their_slot() {
+        // (10 s + overhead) * 5 + changeover = 7.5 minutes
+        let competitors = [competitor("a", Event::Ev333, 10)];
+        let overruns = |minutes| {
+            estimate(
+                round(RoundFormat::AverageOf5, None),
+                vec![scheduled(Event::Ev333, None, minutes, 1)],
+                &competitors,
+            )[0]
+            .1
+        };
+        assert!(!overruns(8));
+        assert!(overruns(7));
+    }
+}
@@ -0,0 +1,369 @@
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::datastructures::{
+    AdvancementCondition, Chances, Competitor, Cutoff, Event, RecordType, ResultValue, Round,
+    RoundFormat,
+};
+
+/// Attempts without a recent history are drawn from this range relative to the PR.
+const FALLBACK_SPREAD: (f64, f64) = (0.9, 1.25);
+
+/// Simulates all rounds of `event` `iterations` times and stores every participant's chances
+/// in `Competitor::chances`. Attempts are sampled from the competitors' recent attempts or,
+/// if they have none, around their PR. Competitors without either are treated as DNF.
+/// Cutoffs and time limits are applied, except for cumulative time limits.
+/// The same seed always leads to the same chances.
+pub fn simulate_event(
+    competitors: &mut [Competitor],
+    event: &Event,
+    rounds: &[Round],
+    iterations: usize,
+    seed: u64,
+) {
+    let default_round = [Round {
+        format: event.default_round_format(),
+        advancement: None,
+        cutoff: None,
+        time_limit: None,
+    }];
+    let rounds = match rounds.is_empty() {
+        true => &default_round[..],
+        false => rounds,
+    };
+    let participants: Vec<_> = (0..competitors.len())
+        .filter(|i| competitors[*i].events.contains(event))
+        .collect();
+    let samplers: Vec<_> = participants
+        .iter()
+        .map(|i| AttemptSampler::new(&competitors[*i], event))
+        .collect();
+
+    // a separate stream per event keeps the chances independent of the order events are simulated in
+    let event_index = Event::ALL
+        .iter()
+        .position(|e| e == event)
+        .unwrap_or_default();
+    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(event_index as u64));
+    let mut counts = vec![Chances::default(); participants.len()];
+    for _ in 0..iterations {
+        let mut remaining: Vec<usize> = (0..participants.len()).collect();
+        for (round_index, round) in rounds.iter().enumerate() {
+            // random order first, so that ties are broken randomly by the stable sort
+            remaining.shuffle(&mut rng);
+            let mut results: Vec<_> = remaining
+                .iter()
+                .map(|p| (round_result(&samplers[*p], round, &mut rng), *p))
+                .collect();
+            results.sort_by_key(|(result, _)| *result);
+
+            let is_final = round_index + 1 == rounds.len();
+            if is_final {
+                for (place, ((result, _), p)) in results.iter().enumerate().take(3) {
+                    if *result != DNF {
+                        counts[*p].podium += 1.0;
+                        if place == 0 {
+                            counts[*p].win += 1.0;
+                        }
+                    }
+                }
+                break;
+            }
+            let advancing =
+                advancing_count(round.advancement.as_ref(), &round.format, &results, event);
+            remaining = results[..advancing].iter().map(|(_, p)| *p).collect();
+            if round_index == 0 {
+                for p in &remaining {
+                    *counts[*p].advance.get_or_insert(0.0) += 1.0;
+                }
+            }
+        }
+    }
+
+    let iterations = iterations.max(1) as f64;
+    for (p, count) in participants.iter().zip(counts) {
+        competitors[*p].chances.insert(
+            *event,
+            Chances {
+                win: count.win / iterations,
+                podium: count.podium / iterations,
+                advance: (rounds.len() > 1).then(|| count.advance.unwrap_or(0.0) / iterations),
+            },
+        );
+    }
+}
+
+/// Value of a DNF attempt or round result, orders behind all valid results.
+const DNF: u64 = u64::MAX;
+
+/// Probability that `competitor` makes the cutoff of a round, based on the same attempt model
+/// as the simulation.
+pub fn cutoff_chance(competitor: &Competitor, event: &Event, cutoff: &Cutoff) -> f64 {
+    let single_chance =
+        AttemptSampler::new(competitor, event).probability_below(cutoff.result.sort_value());
+    1.0 - (1.0 - single_chance).powi(cutoff.attempts as i32)
+}
+
+/// Expected value of a valid attempt of `competitor` as sort value (centiseconds for timed
+/// events), based on the same attempt model as the simulation. `None` without any data.
+pub fn expected_attempt(competitor: &Competitor, event: &Event) -> Option<u64> {
+    match AttemptSampler::new(competitor, event) {
+        AttemptSampler::History(attempts) => {
+            let valid: Vec<_> = attempts.into_iter().flatten().collect();
+            (!valid.is_empty()).then(|| valid.iter().sum::<u64>() / valid.len() as u64)
+        }
+        AttemptSampler::Record(value, true) => {
+            Some((value as f64 * (FALLBACK_SPREAD.0 + FALLBACK_SPREAD.1) / 2.0) as u64)
+        }
+        AttemptSampler::Record(value, false) => Some(value),
+        AttemptSampler::NoData => None,
+    }
+}
+
+/// Result of a round as (ranking value, best attempt), so that tuples order like the round's
+/// ranking. Both are `DNF` if there is no valid result.
+type RoundResult = (u64, u64);
+
+enum AttemptSampler {
+    /// Sort values of recent attempts, `None` for DNFs
+    History(Vec<Option<u64>>),
+    /// Sort value of the PR and whether it can be scaled
+    Record(u64, bool),
+    NoData,
+}
+
+impl AttemptSampler {
+    fn new(competitor: &Competitor, event: &Event) -> Self {
+        if let Some(attempts) = competitor
+            .recent_attempts
+            .get(event)
+            .filter(|attempts| !attempts.is_empty())
+        {
+            return AttemptSampler::History(
+                attempts
+                    .iter()
+                    .map(|attempt| attempt.as_ref().map(ResultValue::sort_value))
+                    .collect(),
+            );
+        }
+        let record = competitor.personal_records.get(event).and_then(|pr| {
+            pr.get(RecordType::Average)
+                .or_else(|| pr.get(RecordType::Single))
+        });
+        match record {
+            // multiblind sort values encode points and time, they cannot be scaled
+            Some(value @ ResultValue::MultiBlind { .. }) => {
+                AttemptSampler::Record(value.sort_value(), false)
+            }
+            Some(value) => AttemptSampler::Record(value.sort_value(), true),
+            None => AttemptSampler::NoData,
+        }
+    }
+
+    /// Probability that a single attempt is better than the sort value `limit`.
+    fn probability_below(&self, limit: u64) -> f64 {
+        match self {
+            AttemptSampler::History(attempts) => {
+                let below = attempts
+                    .iter()
+                    .filter(|attempt| attempt.is_some_and(|value| value < limit))
+                    .count();
+                below as f64 / attempts.len() as f64
+            }
+            AttemptSampler::Record(value, true) => {
+                let low = FALLBACK_SPREAD.0 * *value as f64;
+                let high = FALLBACK_SPREAD.1 * *value as f64;
+                ((limit as f64 - low) / (high - low)).clamp(0.0, 1.0)
+            }
+            AttemptSampler::Record(value, false) => (*value < limit) as u8 as f64,
+            AttemptSampler::NoData => 0.0,
+        }
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> Option<u64> {
+        match self {
+            AttemptSampler::History(attempts) => *attempts.choose(rng)?,
+            AttemptSampler::Record(value, true) => {
+                Some((*value as f64 * rng.gen_range(FALLBACK_SPREAD.0..FALLBACK_SPREAD.1)) as u64)
+            }
+            AttemptSampler::Record(value, false) => Some(*value),
+            AttemptSampler::NoData => None,
+        }
+    }
+}
+
+fn round_result(sampler: &AttemptSampler, round: &Round, rng: &mut StdRng) -> RoundResult {
+    let time_limit = round
+        .time_limit
+        .filter(|limit| !limit.cumulative)
+        .map(|limit| ResultValue::Time(limit.time).sort_value());
+    let mut attempts: Vec<_> = (0..round.format.attempt_count())
+        .map(|_| match sampler.sample(rng) {
+            Some(attempt) if time_limit.is_none_or(|limit| attempt < limit) => attempt,
+            _ => DNF,
+        })
+        .collect();
+    if let Some(cutoff) = &round.cutoff {
+        let cutoff_attempts = (cutoff.attempts as usize).min(attempts.len());
+        let limit = cutoff.result.sort_value();
+        if attempts[..cutoff_attempts]
+            .iter()
+            .all(|attempt| *attempt >= limit)
+        {
+            let best = attempts[..cutoff_attempts]
+                .iter()
+                .min()
+                .copied()
+                .unwrap_or(DNF);
+            return (DNF, best);
+        }
+    }
+    attempts.sort();
+    let best = attempts[0];
+    let counting = match round.format {
+        RoundFormat::BestOf(_) => return (best, best),
+        RoundFormat::MeanOf3 => &attempts[..],
+        RoundFormat::AverageOf5 => &attempts[1..attempts.len() - 1],
+    };
+    match counting.contains(&DNF) {
+        true => (DNF, best),
+        false => (counting.iter().sum::<u64>() / counting.len() as u64, best),
+    }
+}
+
+/// Number of competitors advancing with the given (sorted) round results. Competitors without a
+/// valid result never advance.
+fn advancing_count(
+    condition: Option<&AdvancementCondition>,
+    format: &RoundFormat,
+    results: &[(RoundResult, usize)],
+    event: &Event,
+) -> usize {
+    let count = results.len();
+    let advancing = match condition {
+        Some(AdvancementCondition::Ranking(level)) => *level as usize,
+        Some(AdvancementCondition::Percent(level)) => count * *level as usize / 100,
+        Some(AdvancementCondition::AttemptResult(level)) => {
+            let is_average = !matches!(format, RoundFormat::BestOf(_));
+            let limit = ResultValue::from_wca_value(*event, *level, is_average).sort_value();
+            results
+                .iter()
+                .filter(|((result, _), _)| *result < limit)
+                .count()
+        }
+        None => 0,
+    };
+    let valid = results
+        .iter()
+        .filter(|((result, _), _)| *result != DNF)
+        .count();
+    // regulation 9p1: at least 25% of the competitors are eliminated in every round
+    advancing.min(count * 3 / 4).min(valid)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::datastructures::PersonalRecord;
+
+    fn time(seconds: f64) -> ResultValue {
+        ResultValue::Time(Duration::from_secs_f64(seconds))
+    }
+
+    /// Eight competitors, the first one far faster than everybody else. Every third competitor
+    /// only has a PR instead of recent attempts.
+    fn competitors() -> Vec<Competitor> {
+        (0..8)
+            .map(|index| {
+                let mut competitor = Competitor::new(
+                    format!("Competitor {}", index),
+                    Some(format!("2020TEST{:02}", index)),
+                    vec![Event::Ev333],
+                );
+                let base = match index {
+                    0 => 5.0,
+                    _ => 10.0 + index as f64,
+                };
+                if index % 3 == 2 {
+                    let mut record = PersonalRecord::default();
+                    record.set(RecordType::Average, time(base), None);
+                    competitor.personal_records.insert(Event::Ev333, record);
+                } else {
+                    let attempts = (0..20)
+                        .map(|attempt| match attempt {
+                            7 => None,
+                            _ => Some(time(base + (attempt % 5) as f64 * 0.8)),
+                        })
+                        .collect();
+                    competitor.recent_attempts.insert(Event::Ev333, attempts);
+                }
+                competitor
+            })
+            .collect()
+    }
+
+    fn rounds() -> Vec<Round> {
+        vec![
+            Round {
+                format: RoundFormat::AverageOf5,
+                advancement: Some(AdvancementCondition::Ranking(4)),
+                cutoff: None,
+                time_limit: None,
+            },
+            Round {
+                format: RoundFormat::AverageOf5,
+                advancement: None,
+                cutoff: None,
+                time_limit: None,
+            },
+        ]
+    }
+
+    fn simulated_chances(seed: u64) -> Vec<Chances> {
+        let mut competitors = competitors();
+        simulate_event(&mut competitors, &Event::Ev333, &rounds(), 500, seed);
+        competitors
+            .iter()
+            .map(|comp| comp.chances[&Event::Ev333])
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_chances() {
+        assert_eq!(simulated_chances(42), simulated_chances(42));
+    }
+
+    #[test]
+    fn chances_are_probabilities_that_add_up() {
+        let chances = simulated_chances(7);
+        for chance in &chances {
+            assert!((0.0..=1.0).contains(&chance.win));
+            assert!((0.0..=1.0).contains(&chance.podium));
+            assert!(chance.win <= chance.podium);
+            assert!((0.0..=1.0).contains(&chance.advance.unwrap()));
+        }
+        let sum = |value: fn(&Chances) -> f64| chances.iter().map(value).sum::<f64>();
+        // one winner, three podium places and four advancing competitors per simulation
+        assert!((sum(|chance| chance.win) - 1.0).abs() < 1e-9);
+        assert!((sum(|chance| chance.podium) - 3.0).abs() < 1e-9);
+        assert!((sum(|chance| chance.advance.unwrap()) - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn dominant_competitor_wins() {
+        let chances = simulated_chances(3);
+        // only two DNFs in one average stop the fastest competitor
+        assert!(chances[0].win > 0.9);
+        assert!(chances[0].podium > 0.9);
+        assert!(chances[1..].iter().all(|chance| chance.win < 0.1));
+    }
+
+    #[test]
+    fn single_round_has_no_advancement_chance() {
+        let mut competitors = competitors();
+        simulate_event(&mut competitors, &Event::Ev333, &[], 100, 1);
+        assert_eq!(competitors[0].chances[&Event::Ev333].advance, None);
+    }
+}
@@ -1,5 +1,6 @@
 use thiserror::Error;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum WCOError {
     #[error("Invalid input: {0}")]
@@ -14,6 +15,12 @@ pub enum WCOError {
     #[error("JSON parsing error: {0}")]
     JsonError(#[from] serde_json::Error),
 
+    #[error("Archive error: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+
+    #[error("Offline mode: {0} is not available in the cache")]
+    CacheMiss(String),
+
     #[error("Plotting error: {0}")]
     PlottingError(String),
 }
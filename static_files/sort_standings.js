@@ -0,0 +1,18 @@
+// Reorders the ranked rows of an event table by their position in the clicked ordering
+// ("single" or "average"). Rows without a position stay at the bottom in their order.
+function sortStandings(button, key) {
+    const table = button.closest("table");
+    const rows = Array.from(table.querySelectorAll("tr")).slice(1);
+    const ranked = rows.filter((row) => row.dataset[key] !== undefined);
+    const unranked = rows.filter((row) => row.dataset[key] === undefined);
+    ranked.sort((a, b) => a.dataset[key] - b.dataset[key]);
+    for (const row of ranked.concat(unranked)) {
+        row.parentNode.appendChild(row);
+    }
+    for (const row of ranked) {
+        row.cells[0].textContent = row.dataset[key];
+    }
+    for (const other of table.querySelectorAll("button.sort")) {
+        other.classList.toggle("active", other === button);
+    }
+}